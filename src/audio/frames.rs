@@ -0,0 +1,111 @@
+//! The per-frame work of a call's audio threads, kept apart from the device
+//! bridge so it can be exercised without audio hardware.
+
+use crate::playback::AudioInjector;
+use crate::recording::{RecordingTap, TapFrame};
+
+/// What [`AudioBridge`](super::AudioBridge) does with each 20 ms frame,
+/// wired into the device bridge's frame processor and tap.
+pub trait FrameHooks: Send + Sync {
+    /// Change a frame before it is sent (`local`, after muting) or played.
+    fn process(&self, local: bool, samples: &mut [i16], sample_rate: u32);
+    /// See a frame once [`process`](Self::process) is done with it.
+    fn tap(&self, local: bool, samples: &[i16], sample_rate: u32);
+}
+
+/// A call's frame hooks: files played into the call, then the recording,
+/// so played clips are recorded too.
+#[derive(Debug, Clone, Default)]
+pub struct CallFrames {
+    pub injector: AudioInjector,
+    pub recording: RecordingTap,
+}
+
+impl FrameHooks for CallFrames {
+    fn process(&self, local: bool, samples: &mut [i16], sample_rate: u32) {
+        if local {
+            self.injector.process_outgoing(samples, sample_rate);
+        } else {
+            self.injector.process_incoming(samples, sample_rate);
+        }
+    }
+
+    fn tap(&self, local: bool, samples: &[i16], sample_rate: u32) {
+        self.recording.send(TapFrame {
+            local,
+            sample_rate,
+            samples: samples.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::{AudioClip, PlaybackMode, PlaybackOptions};
+    use crate::recording::{Recorder, RecordingChannels, RecordingMetadata, RecordingPolicy};
+
+    fn play(frames: &CallFrames, mode: PlaybackMode) {
+        let clip = AudioClip {
+            sample_rate: 8000,
+            samples: vec![1000; 8000],
+        };
+        frames.injector.play(
+            clip,
+            PlaybackOptions {
+                mode,
+                local: true,
+                looped: false,
+            },
+            8000,
+        );
+    }
+
+    #[test]
+    fn played_files_replace_or_join_the_mic_and_are_heard_locally() {
+        let frames = CallFrames::default();
+        play(&frames, PlaybackMode::Replace);
+        let mut mic = [300; 160];
+        frames.process(true, &mut mic, 8000);
+        assert_eq!(mic, [1000; 160]);
+        let mut speaker = [300; 160];
+        frames.process(false, &mut speaker, 8000);
+        assert_eq!(speaker, [1300; 160]);
+
+        play(&frames, PlaybackMode::Mix);
+        let mut mic = [300; 160];
+        frames.process(true, &mut mic, 8000);
+        assert_eq!(mic, [1300; 160]);
+    }
+
+    #[test]
+    fn tapped_frames_reach_the_recording() {
+        let frames = CallFrames::default();
+        let directory = std::env::temp_dir().join(format!("sip-client-frames-{}", std::process::id()));
+        let policy = RecordingPolicy {
+            channels: RecordingChannels::Mono,
+            directory: directory.clone(),
+            ..Default::default()
+        };
+        let metadata = RecordingMetadata {
+            call_id: "call-1".to_string(),
+            remote: "Alice".to_string(),
+            remote_uri: None,
+            is_incoming: false,
+            account: None,
+            started: chrono::Local::now(),
+        };
+        let recorder = Recorder::start(&frames.recording, &policy, metadata).unwrap();
+        frames.tap(true, &[100; 160], 8000);
+        frames.tap(false, &[20; 160], 8000);
+        let path = recorder.finish().unwrap();
+        // Without a recorder, frames are dropped
+        frames.tap(true, &[100; 160], 8000);
+
+        let wav = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        let data = wav.windows(4).position(|id| id == b"data").unwrap() + 8;
+        let samples: Vec<i16> = wav[data..].chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        assert_eq!(samples, [120; 160]);
+    }
+}
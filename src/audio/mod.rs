@@ -12,31 +12,55 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use rvoip::sip::AudioStream;
-use rvoip_audio_device::{AudioLevel, DeviceBridge, DeviceOptions};
+use rvoip_audio_device::{AudioLevel, DeviceBridge, DeviceOptions, OutputOptions, ProcessedFrame, TappedFrame};
 
 use crate::event_channel::SipEvent;
+use crate::playback::{AudioClip, AudioInjector, PlaybackOptions};
+use crate::tones::ToneSource;
+
+mod frames;
+
+pub use frames::{CallFrames, FrameHooks};
 
 // Re-export the device-bridge surface the rest of the client refers to via
-// `crate::audio::*` (direction enum, device enumeration, live-bridge handle).
-pub use rvoip_audio_device::{list_devices, AudioDirection, RunningAudio};
+// `crate::audio::*` (direction enum, device enumeration, live-bridge and
+// tone-output handles).
+pub use rvoip_audio_device::{list_devices, AudioDirection, OutputStream, RunningAudio};
+
+/// State the caller shares with a running bridge.
+#[derive(Clone)]
+pub struct BridgeHooks {
+    /// While set, the mic pump emits silence (the rvoip `mute()` only
+    /// signals).
+    pub muted: Arc<AtomicBool>,
+    /// While clear, the mic is neither captured nor sent, for receive-only
+    /// early media.
+    pub sending: Arc<AtomicBool>,
+    /// Receives [`SipEvent::AudioLevel`] updates for VU meters.
+    pub event_tx: Option<mpsc::UnboundedSender<SipEvent>>,
+    /// Processes, then taps, every frame in both directions.
+    pub frames: Arc<dyn FrameHooks>,
+}
 
 /// Starts the cpal bridge for a call.
 pub struct AudioBridge;
 
 impl AudioBridge {
-    /// Start capturing/playing `audio` via [`rvoip_audio_device`].
-    ///
-    /// `muted` is shared with the caller; while set, the mic pump emits silence
-    /// (the rvoip `mute()` only signals). `event_tx`, when present, receives
-    /// [`SipEvent::AudioLevel`] updates for VU meters.
+    /// Start capturing/playing `audio` via [`rvoip_audio_device`], wired to
+    /// the caller through `hooks`.
     pub fn start(
         audio: AudioStream,
         input_device: Option<String>,
         output_device: Option<String>,
-        muted: Arc<AtomicBool>,
-        event_tx: Option<mpsc::UnboundedSender<SipEvent>>,
+        hooks: BridgeHooks,
     ) -> anyhow::Result<RunningAudio> {
-        let mut opts = DeviceOptions::new().with_mute_flag(muted);
+        let BridgeHooks {
+            muted,
+            sending,
+            event_tx,
+            frames,
+        } = hooks;
+        let mut opts = DeviceOptions::new().with_mute_flag(muted).with_send_flag(sending);
         if let Some(device) = input_device {
             opts = opts.with_input_device(device);
         }
@@ -51,6 +75,43 @@ impl AudioBridge {
                 });
             });
         }
+        let processor = frames.clone();
+        opts = opts.with_frame_processor(move |frame: &mut ProcessedFrame| {
+            let local = frame.direction == AudioDirection::Input;
+            processor.process(local, frame.samples, frame.sample_rate);
+        });
+        opts = opts.with_frame_tap(move |frame: TappedFrame| {
+            frames.tap(frame.direction == AudioDirection::Input, &frame.samples, frame.sample_rate);
+        });
         DeviceBridge::start(audio, opts)
     }
 }
+
+/// Plays client-generated sound (ringtone, ringback) on an output device
+/// while no call audio is running. Playback stops when the returned stream
+/// is dropped.
+pub struct ToneOutput;
+
+impl ToneOutput {
+    pub fn start(device: Option<String>, mut source: ToneSource) -> anyhow::Result<OutputStream> {
+        let mut opts = OutputOptions::new();
+        if let Some(device) = device {
+            opts = opts.with_device(device);
+        }
+        OutputStream::start(opts, move |frame: &mut [i16], sample_rate: u32| source.fill(frame, sample_rate))
+    }
+
+    /// Loop `clip` on `device`. It starts once the output is open, so it is
+    /// resampled to the device's rate up front rather than on its thread.
+    pub fn start_file(device: Option<String>, clip: AudioClip) -> anyhow::Result<OutputStream> {
+        let injector = AudioInjector::default();
+        let output = Self::start(device, ToneSource::File(injector.clone()))?;
+        let options = PlaybackOptions {
+            local: true,
+            looped: true,
+            ..Default::default()
+        };
+        injector.play(clip, options, output.sample_rate());
+        Ok(output)
+    }
+}
@@ -0,0 +1,119 @@
+//! In-memory list of recent calls for the call screen.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::call_stats::MediaStats;
+use crate::caller_id::CallerIdentity;
+use crate::sip_client::CallInfo;
+
+/// Entries kept; older calls are dropped.
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// Connected, then ended by either side.
+    Completed,
+    /// Incoming call that was never answered.
+    Missed,
+    /// Incoming call we rejected.
+    Declined,
+    /// Outgoing call ended before it was answered.
+    NoAnswer,
+    /// Outgoing call refused by the far end (SIP status code).
+    Failed(u16),
+    /// Handed to another party by a transfer.
+    Transferred,
+    /// Refused or hung up because media could not be encrypted while the
+    /// SRTP policy is mandatory.
+    Unencrypted,
+}
+
+impl CallOutcome {
+    /// Outcome for a call that ended without a failure response.
+    /// `declined` is set when we hung up a ringing incoming call.
+    pub fn ended(call: &CallInfo, declined: bool) -> Self {
+        match (call.connected_at.is_some(), call.is_incoming) {
+            (true, _) => Self::Completed,
+            (false, true) if declined => Self::Declined,
+            (false, true) => Self::Missed,
+            (false, false) => Self::NoAnswer,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Completed => "Completed".to_string(),
+            Self::Missed => "Missed".to_string(),
+            Self::Declined => "Declined".to_string(),
+            Self::NoAnswer => "No answer".to_string(),
+            Self::Failed(code) => format!("Failed ({})", code),
+            Self::Transferred => "Transferred".to_string(),
+            Self::Unencrypted => "Rejected (unencrypted)".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallRecord {
+    pub identity: CallerIdentity,
+    pub is_incoming: bool,
+    /// When the call connected, or ended if it never did.
+    pub at: DateTime<Utc>,
+    pub duration: Option<Duration>,
+    pub outcome: CallOutcome,
+    /// Media quality as of the last RTCP report
+    pub stats: Option<MediaStats>,
+    /// pcapng saved automatically for a failed call
+    pub capture: Option<PathBuf>,
+    /// Recordings made during the call, in order
+    pub recordings: Vec<PathBuf>,
+}
+
+impl CallRecord {
+    pub fn new(call: &CallInfo, outcome: CallOutcome) -> Self {
+        let duration = call
+            .connected_at
+            .and_then(|start| (Utc::now() - start).to_std().ok());
+        Self {
+            identity: call.identity.clone(),
+            is_incoming: call.is_incoming,
+            at: call.connected_at.unwrap_or_else(Utc::now),
+            duration,
+            outcome,
+            stats: call.stats.clone(),
+            capture: None,
+            recordings: Vec::new(),
+        }
+    }
+}
+
+/// Most recent call first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CallHistory {
+    entries: VecDeque<CallRecord>,
+}
+
+impl CallHistory {
+    /// Add a call; returns the new entry for callers that attach more to it.
+    pub fn record(&mut self, call: &CallInfo, outcome: CallOutcome) -> &mut CallRecord {
+        self.entries.truncate(MAX_ENTRIES - 1);
+        self.entries.push_front(CallRecord::new(call, outcome));
+        &mut self.entries[0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CallRecord> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
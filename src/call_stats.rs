@@ -0,0 +1,345 @@
+//! Per-call media quality from RTCP sender/receiver reports (RFC 3550 §6.4)
+//! plus the local jitter buffer, with an E-model MOS estimate and sustained
+//! degradation alerts.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// Snapshot of a call's media quality, refreshed on every RTCP report.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaStats {
+    pub packets_sent: u64,
+    pub packets_received: u64,
+    /// Cumulative packets lost in the inbound stream.
+    pub packets_lost: u64,
+    /// Inbound interarrival jitter in ms.
+    pub jitter_ms: f32,
+    /// Round-trip time from RTCP LSR/DLSR; `None` until the remote has
+    /// reported on one of our sender reports.
+    pub rtt_ms: Option<f32>,
+    /// Negotiated codec name, filled in by the UI from the call's media.
+    pub codec: Option<String>,
+    /// Playout delay held by the audio bridge's jitter buffer, in ms.
+    pub jitter_buffer_ms: Option<u32>,
+}
+
+impl MediaStats {
+    /// Inbound loss in percent of expected packets.
+    pub fn loss_percent(&self) -> f32 {
+        let expected = self.packets_received + self.packets_lost;
+        if expected == 0 {
+            return 0.0;
+        }
+        self.packets_lost as f32 * 100.0 / expected as f32
+    }
+
+    /// Estimated MOS (1.0–4.5) from a simplified ITU-T G.107 E-model:
+    /// one-way delay is half the RTT plus jitter and buffering, and loss
+    /// costs 2.5 R per percent.
+    pub fn mos(&self) -> f32 {
+        let buffering = self
+            .jitter_buffer_ms
+            .map(|ms| ms as f32)
+            .unwrap_or(self.jitter_ms * 2.0);
+        let delay = self.rtt_ms.unwrap_or(0.0) / 2.0 + buffering + 10.0;
+        let delay_impairment = if delay < 160.0 {
+            delay / 40.0
+        } else {
+            (delay - 120.0) / 10.0
+        };
+        let r = (93.2 - delay_impairment - self.loss_percent() * 2.5).clamp(0.0, 100.0);
+        (1.0 + 0.035 * r + 7.0e-6 * r * (r - 60.0) * (100.0 - r)).clamp(1.0, 4.5)
+    }
+
+    /// One-word rating of [`mos`](Self::mos).
+    pub fn rating(&self) -> &'static str {
+        match self.mos() {
+            m if m >= 4.2 => "Excellent",
+            m if m >= 3.8 => "Good",
+            m if m >= 3.3 => "Fair",
+            m if m >= 2.6 => "Poor",
+            _ => "Bad",
+        }
+    }
+}
+
+/// When a call counts as degraded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityThresholds {
+    pub max_loss_percent: f32,
+    pub max_jitter_ms: f32,
+    pub min_mos: f32,
+    /// How long thresholds must stay crossed before the user is warned, so
+    /// a single lost burst does not flash a warning.
+    pub sustain: Duration,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            max_loss_percent: 5.0,
+            max_jitter_ms: 50.0,
+            min_mos: 3.3,
+            sustain: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Quality over the span between two RTCP reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalQuality {
+    /// When the report closing the interval arrived.
+    pub at: DateTime<Utc>,
+    pub loss_percent: f32,
+    pub jitter_ms: f32,
+    pub rtt_ms: Option<f32>,
+    pub mos: f32,
+}
+
+/// Watches a call's RTCP snapshots for sustained degradation and keeps the
+/// worst interval for the hangup summary.
+#[derive(Debug, Clone)]
+pub struct QualityMonitor {
+    thresholds: QualityThresholds,
+    previous: Option<MediaStats>,
+    last_report: Option<Instant>,
+    bad_since: Option<Instant>,
+    worst: Option<IntervalQuality>,
+    reports: u32,
+    bad_reports: u32,
+}
+
+impl QualityMonitor {
+    pub fn new(thresholds: QualityThresholds) -> Self {
+        Self {
+            thresholds,
+            previous: None,
+            last_report: None,
+            bad_since: None,
+            worst: None,
+            reports: 0,
+            bad_reports: 0,
+        }
+    }
+
+    /// Feed the latest cumulative snapshot. Returns the warning to show
+    /// while thresholds have been crossed for at least `sustain`.
+    pub fn update(&mut self, stats: &MediaStats, now: Instant) -> Option<String> {
+        let interval = self.interval(stats);
+        let interval_start = self.last_report.unwrap_or(now);
+        self.previous = Some(stats.clone());
+        self.last_report = Some(now);
+        self.reports += 1;
+        if self.worst.as_ref().is_none_or(|worst| interval.mos < worst.mos) {
+            self.worst = Some(interval.clone());
+        }
+
+        let problems = self.problems(&interval);
+        if problems.is_empty() {
+            self.bad_since = None;
+            return None;
+        }
+        self.bad_reports += 1;
+        // The report describes the interval since the previous one
+        let since = *self.bad_since.get_or_insert(interval_start);
+        (now.duration_since(since) >= self.thresholds.sustain)
+            .then(|| format!("Poor connection: {}", problems.join(", ")))
+    }
+
+    /// One-line quality summary for the log at hangup; `None` if no RTCP
+    /// report ever arrived.
+    pub fn summary(&self, call_id: &str) -> Option<String> {
+        let last = self.previous.as_ref()?;
+        let mut summary = format!(
+            "Call {} quality: {} RTCP reports ({} degraded), overall MOS {:.2}, loss {:.1}% ({}/{} packets), sent {}",
+            call_id,
+            self.reports,
+            self.bad_reports,
+            last.mos(),
+            last.loss_percent(),
+            last.packets_lost,
+            last.packets_received + last.packets_lost,
+            last.packets_sent,
+        );
+        if let Some(worst) = &self.worst {
+            summary.push_str(&format!(
+                "; worst interval ending {}: MOS {:.2}, loss {:.1}%, jitter {:.1} ms, RTT {}",
+                worst.at.to_rfc3339(),
+                worst.mos,
+                worst.loss_percent,
+                worst.jitter_ms,
+                worst
+                    .rtt_ms
+                    .map(|ms| format!("{:.0} ms", ms))
+                    .unwrap_or_else(|| "n/a".to_string()),
+            ));
+        }
+        Some(summary)
+    }
+
+    /// Stats for the span since the previous report (cumulative counters
+    /// differenced; jitter and RTT are already point estimates).
+    fn interval(&self, stats: &MediaStats) -> IntervalQuality {
+        let delta = match &self.previous {
+            Some(prev) => MediaStats {
+                packets_received: stats.packets_received.saturating_sub(prev.packets_received),
+                packets_lost: stats.packets_lost.saturating_sub(prev.packets_lost),
+                ..stats.clone()
+            },
+            None => stats.clone(),
+        };
+        IntervalQuality {
+            at: Utc::now(),
+            loss_percent: delta.loss_percent(),
+            jitter_ms: delta.jitter_ms,
+            rtt_ms: delta.rtt_ms,
+            mos: delta.mos(),
+        }
+    }
+
+    fn problems(&self, interval: &IntervalQuality) -> Vec<String> {
+        let limits = &self.thresholds;
+        let mut problems = Vec::new();
+        if interval.loss_percent > limits.max_loss_percent {
+            problems.push(format!("{:.0}% packet loss", interval.loss_percent));
+        }
+        if interval.jitter_ms > limits.max_jitter_ms {
+            problems.push(format!("{:.0} ms jitter", interval.jitter_ms));
+        }
+        if interval.mos < limits.min_mos && problems.is_empty() {
+            problems.push(format!("MOS {:.1}", interval.mos));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100 expected packets, `lost` of them lost, with 2 ms of jitter.
+    fn stats(lost: u64) -> MediaStats {
+        MediaStats {
+            packets_received: 100 - lost,
+            packets_lost: lost,
+            jitter_ms: 2.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn loss_is_a_share_of_expected_packets() {
+        assert_eq!(MediaStats::default().loss_percent(), 0.0);
+        assert_eq!(stats(5).loss_percent(), 5.0);
+        assert_eq!(stats(100).loss_percent(), 100.0);
+    }
+
+    #[test]
+    fn mos_follows_the_e_model() {
+        assert!((stats(0).mos() - 4.4).abs() < 0.01, "{}", stats(0).mos());
+        // Total loss bottoms out rather than going negative
+        assert_eq!(stats(50).mos(), 1.0);
+        assert_eq!(stats(100).mos(), 1.0);
+        // Long one-way delay costs more per ms past the knee
+        let delayed = MediaStats {
+            rtt_ms: Some(600.0),
+            ..stats(0)
+        };
+        assert!(delayed.mos() < 3.8, "{}", delayed.mos());
+        // A configured jitter buffer replaces the jitter estimate
+        let buffered = MediaStats {
+            jitter_buffer_ms: Some(400),
+            ..stats(0)
+        };
+        assert!(buffered.mos() < delayed.mos());
+    }
+
+    #[test]
+    fn ratings_change_at_the_mos_thresholds() {
+        for (lost, rating) in [
+            (0, "Excellent"),
+            (3, "Excellent"),
+            (4, "Good"),
+            (7, "Good"),
+            (8, "Fair"),
+            (10, "Fair"),
+            (12, "Poor"),
+            (16, "Poor"),
+            (20, "Bad"),
+            (50, "Bad"),
+        ] {
+            let stats = stats(lost);
+            assert_eq!(stats.rating(), rating, "{}% loss, MOS {}", lost, stats.mos());
+        }
+    }
+
+    /// Feeds a monitor reports every 5 s, each covering 100 packets.
+    struct Reports {
+        monitor: QualityMonitor,
+        totals: MediaStats,
+        start: Instant,
+        elapsed: Duration,
+    }
+
+    impl Reports {
+        fn new() -> Self {
+            Self {
+                monitor: QualityMonitor::new(QualityThresholds::default()),
+                totals: MediaStats {
+                    jitter_ms: 2.0,
+                    ..Default::default()
+                },
+                start: Instant::now(),
+                elapsed: Duration::ZERO,
+            }
+        }
+
+        /// The next report, with `lost` of its 100 packets lost.
+        fn report(&mut self, lost: u64) -> Option<String> {
+            self.totals.packets_received += 100 - lost;
+            self.totals.packets_lost += lost;
+            let warning = self.monitor.update(&self.totals, self.start + self.elapsed);
+            self.elapsed += Duration::from_secs(5);
+            warning
+        }
+    }
+
+    #[test]
+    fn a_breach_shorter_than_the_sustain_time_does_not_warn() {
+        let mut reports = Reports::new();
+        assert_eq!(reports.report(0), None);
+        assert_eq!(reports.report(20), None);
+        assert_eq!(reports.report(0), None);
+        assert_eq!(reports.report(20), None);
+    }
+
+    #[test]
+    fn a_sustained_breach_warns_until_it_recovers() {
+        let mut reports = Reports::new();
+        assert_eq!(reports.report(0), None);
+        assert_eq!(reports.report(20), None);
+        assert_eq!(reports.report(20).as_deref(), Some("Poor connection: 20% packet loss"));
+        assert!(reports.report(20).is_some());
+
+        // Recovery clears the warning, and a new breach starts the wait over
+        assert_eq!(reports.report(0), None);
+        assert_eq!(reports.report(20), None);
+    }
+
+    #[test]
+    fn the_summary_reports_the_worst_interval() {
+        let mut reports = Reports::new();
+        assert_eq!(reports.monitor.summary("call-1"), None);
+        reports.report(0);
+        reports.report(50);
+        reports.report(10);
+        reports.report(0);
+
+        let summary = reports.monitor.summary("call-1").unwrap();
+        assert!(summary.starts_with("Call call-1 quality: 4 RTCP reports (2 degraded)"), "{}", summary);
+        assert!(summary.contains("loss 15.0% (60/400 packets)"), "{}", summary);
+        assert!(summary.contains("worst interval ending"), "{}", summary);
+        assert!(summary.contains(": MOS 1.00, loss 50.0%, jitter 2.0 ms, RTT n/a"), "{}", summary);
+    }
+}
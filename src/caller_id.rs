@@ -0,0 +1,355 @@
+//! Caller identity extraction for incoming (and display of outgoing) calls.
+//!
+//! Network-asserted identity wins over what the caller put in `From`:
+//! P-Asserted-Identity (RFC 3325), then Remote-Party-ID (the pre-standard
+//! draft many PBXs still send), then `From`. `Privacy: id`/`header`/`user`
+//! (RFC 3323), RPID `privacy=` and the anonymous From of RFC 3323 §4.1.1 all
+//! make the caller show as "Anonymous".
+
+use crate::sip_uri::{NameAddr, Scheme, SipUri};
+
+/// `From` of a call that withholds caller ID (RFC 3323 §4.1.1).
+pub const ANONYMOUS_FROM: &str = "\"Anonymous\" <sip:anonymous@anonymous.invalid>";
+
+/// Where the identity shown for a call came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdentitySource {
+    #[default]
+    From,
+    PAssertedIdentity,
+    RemotePartyId,
+    /// The target we dialed (outgoing calls).
+    Dialed,
+}
+
+/// Who is on the other end of a call, as shown on screens and in history.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CallerIdentity {
+    pub display_name: Option<String>,
+    pub uri: Option<SipUri>,
+    /// Telephone number (user part of a `tel:`/`user=phone`/numeric URI).
+    pub number: Option<String>,
+    pub anonymous: bool,
+    pub source: IdentitySource,
+}
+
+impl CallerIdentity {
+    /// Identity of an incoming call from its `From` value and request headers.
+    pub fn from_headers(from: &str, headers: &[(String, String)]) -> Self {
+        let header = |name: &'static str| header_values(headers, name);
+
+        let privacy_id = header("Privacy")
+            .flat_map(|v| v.split(';'))
+            .map(str::trim)
+            .any(|p| ["id", "header", "user"].iter().any(|k| p.eq_ignore_ascii_case(k)));
+
+        let asserted = header("P-Asserted-Identity")
+            .flat_map(split_header_list)
+            .filter_map(|v| NameAddr::parse(v).ok())
+            // The first entry carrying a display name, else the first one
+            // (min_by_key keeps the earliest of equal keys)
+            .min_by_key(|a| a.display_name.is_none())
+            .map(|a| Self::from_name_addr(a, IdentitySource::PAssertedIdentity));
+
+        let rpid = || {
+            header("Remote-Party-ID")
+                .flat_map(split_header_list)
+                .filter_map(|v| NameAddr::parse(v).ok())
+                // party=called entries describe the callee, not us
+                .find(|a| !param_is(&a.params, "party", "called"))
+                .map(|a| {
+                    let hidden = a.params.iter().any(|(n, v)| {
+                        n.eq_ignore_ascii_case("privacy")
+                            && v.as_deref().is_some_and(|v| !v.eq_ignore_ascii_case("off"))
+                    });
+                    let mut identity = Self::from_name_addr(a, IdentitySource::RemotePartyId);
+                    identity.anonymous |= hidden;
+                    identity
+                })
+        };
+
+        let mut identity = asserted
+            .or_else(rpid)
+            .or_else(|| NameAddr::parse(from).ok().map(|a| Self::from_name_addr(a, IdentitySource::From)))
+            .unwrap_or_default();
+        identity.anonymous |= privacy_id;
+        identity
+    }
+
+    /// Identity of a target we dialed.
+    pub fn from_target(target: &str) -> Self {
+        match NameAddr::parse(target) {
+            Ok(addr) => Self::from_name_addr(addr, IdentitySource::Dialed),
+            Err(_) => Self {
+                display_name: Some(target.trim().to_string()),
+                source: IdentitySource::Dialed,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn from_name_addr(addr: NameAddr, source: IdentitySource) -> Self {
+        let uri = addr.uri;
+        let number = uri
+            .user
+            .as_deref()
+            .map(|u| u.split(';').next().unwrap_or(u))
+            .filter(|u| uri.is_phone_number() || is_numeric(u))
+            .map(str::to_string);
+        let anonymous = is_anonymous_uri(&uri)
+            || addr
+                .display_name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case("anonymous"));
+        Self {
+            display_name: addr.display_name,
+            uri: Some(uri),
+            number,
+            anonymous,
+            source,
+        }
+    }
+
+    /// Primary line: the name, else the formatted number, else the address.
+    pub fn label(&self) -> String {
+        if self.anonymous {
+            return "Anonymous".to_string();
+        }
+        if let Some(name) = &self.display_name {
+            return name.clone();
+        }
+        if let Some(number) = &self.number {
+            return format_number(number);
+        }
+        match &self.uri {
+            Some(uri) => uri.without_scheme(),
+            None => "Unknown".to_string(),
+        }
+    }
+
+    /// Secondary line under [`label`](Self::label): the number or address
+    /// when a name is shown, else nothing.
+    pub fn detail(&self) -> Option<String> {
+        if self.anonymous || self.display_name.is_none() {
+            return None;
+        }
+        match (&self.number, &self.uri) {
+            (Some(number), _) => Some(format_number(number)),
+            (None, Some(uri)) => Some(uri.without_scheme()),
+            _ => None,
+        }
+    }
+
+    /// Whether the identity was asserted by the network (PAI/RPID) rather
+    /// than taken from the caller-supplied `From`.
+    pub fn is_network_asserted(&self) -> bool {
+        matches!(
+            self.source,
+            IdentitySource::PAssertedIdentity | IdentitySource::RemotePartyId
+        )
+    }
+
+    /// Address to call back, unless the caller withheld it.
+    pub fn callback(&self) -> Option<String> {
+        if self.anonymous {
+            return None;
+        }
+        self.uri.as_ref().map(|uri| match uri.scheme {
+            Scheme::Tel => uri.to_string(),
+            _ => uri.without_scheme(),
+        })
+    }
+}
+
+/// Group a telephone number for display: NANP as `+1 (415) 555-0100`, other
+/// E.164 numbers as `+44 207 946 0000`, short numbers unchanged.
+pub fn format_number(number: &str) -> String {
+    let Some(digits) = number.strip_prefix('+') else {
+        return number.to_string();
+    };
+    if !is_numeric(digits) {
+        return number.to_string();
+    }
+    if let Some(national) = digits.strip_prefix('1').filter(|n| n.len() == 10) {
+        return format!("+1 ({}) {}-{}", &national[..3], &national[3..6], &national[6..]);
+    }
+    let cc_len = country_code_len(digits);
+    if digits.len() <= cc_len + 4 {
+        return number.to_string();
+    }
+    let (cc, rest) = digits.split_at(cc_len);
+    let mut groups = Vec::new();
+    let mut rest = rest;
+    while rest.len() > 4 {
+        groups.push(&rest[..3]);
+        rest = &rest[3..];
+    }
+    groups.push(rest);
+    format!("+{} {}", cc, groups.join(" "))
+}
+
+/// Length of the ITU-T E.164 country code at the start of `digits`.
+fn country_code_len(digits: &str) -> usize {
+    const TWO_DIGIT: &[&str] = &[
+        "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46",
+        "47", "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63",
+        "64", "65", "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98",
+    ];
+    if digits.starts_with('1') || digits.starts_with('7') {
+        1
+    } else if TWO_DIGIT.iter().any(|cc| digits.starts_with(cc)) {
+        2
+    } else {
+        3
+    }
+}
+
+fn header_values<'a>(headers: &'a [(String, String)], name: &'a str) -> impl Iterator<Item = &'a str> {
+    headers
+        .iter()
+        .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn is_numeric(value: &str) -> bool {
+    let digits = value.strip_prefix('+').unwrap_or(value);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_anonymous_uri(uri: &SipUri) -> bool {
+    uri.host.eq_ignore_ascii_case("anonymous.invalid")
+        || uri
+            .user
+            .as_deref()
+            .is_some_and(|u| u.eq_ignore_ascii_case("anonymous"))
+}
+
+fn param_is(params: &[(String, Option<String>)], name: &str, value: &str) -> bool {
+    params.iter().any(|(n, v)| {
+        n.eq_ignore_ascii_case(name) && v.as_deref().is_some_and(|v| v.eq_ignore_ascii_case(value))
+    })
+}
+
+/// Split a comma-separated header list, ignoring commas inside quotes or
+/// angle brackets (`"Doe, John" <sip:...>, <tel:+1...>`).
+fn split_header_list(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut angle) = (0, false, false);
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            ',' if !quoted && !angle => {
+                parts.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(value[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn first_named_asserted_identity_wins() {
+        let identity = CallerIdentity::from_headers(
+            "<sip:200@pbx>",
+            &headers(&[
+                ("P-Asserted-Identity", "<tel:+14155550100>"),
+                ("P-Asserted-Identity", "\"Alice\" <sip:alice@pbx>, \"Alice Mobile\" <sip:+14155550199@pbx>"),
+            ]),
+        );
+        assert_eq!(identity.display_name.as_deref(), Some("Alice"));
+        assert_eq!(identity.source, IdentitySource::PAssertedIdentity);
+    }
+
+    #[test]
+    fn first_asserted_identity_wins_when_none_is_named() {
+        let identity = CallerIdentity::from_headers(
+            "<sip:200@pbx>",
+            &headers(&[("P-Asserted-Identity", "<sip:+14155550100@pbx;user=phone>, <tel:+14155550199>")]),
+        );
+        assert_eq!(identity.number.as_deref(), Some("+14155550100"));
+    }
+
+    #[test]
+    fn privacy_id_hides_any_identity() {
+        for privacy in ["id", "header;critical", "user", "ID"] {
+            let identity = CallerIdentity::from_headers(
+                "\"Alice\" <sip:alice@pbx>",
+                &headers(&[("P-Asserted-Identity", "<tel:+14155550100>"), ("Privacy", privacy)]),
+            );
+            assert!(identity.anonymous, "{}", privacy);
+            assert_eq!(identity.label(), "Anonymous");
+            assert_eq!(identity.detail(), None);
+            assert_eq!(identity.callback(), None);
+        }
+        let identity = CallerIdentity::from_headers("<sip:alice@pbx>", &headers(&[("Privacy", "none")]));
+        assert!(!identity.anonymous);
+
+        let identity = CallerIdentity::from_headers(ANONYMOUS_FROM, &[]);
+        assert!(identity.anonymous);
+        assert_eq!(identity.label(), "Anonymous");
+    }
+
+    #[test]
+    fn remote_party_id_is_used_without_an_asserted_identity() {
+        let identity = CallerIdentity::from_headers(
+            "\"Front Desk\" <sip:200@pbx>",
+            &headers(&[(
+                "Remote-Party-ID",
+                "\"Reception\" <sip:100@pbx>;party=called, \"Bob\" <sip:+442079460000@pbx;user=phone>;party=calling;screen=yes",
+            )]),
+        );
+        assert_eq!(identity.source, IdentitySource::RemotePartyId);
+        assert!(identity.is_network_asserted());
+        assert_eq!(identity.display_name.as_deref(), Some("Bob"));
+        assert_eq!(identity.number.as_deref(), Some("+442079460000"));
+        assert_eq!(identity.detail().as_deref(), Some("+44 207 946 0000"));
+
+        for (privacy, anonymous) in [("full", true), ("name", true), ("off", false)] {
+            let identity = CallerIdentity::from_headers(
+                "<sip:200@pbx>",
+                &headers(&[("Remote-Party-ID", &format!("\"Bob\" <sip:bob@pbx>;privacy={}", privacy))]),
+            );
+            assert_eq!(identity.anonymous, anonymous, "privacy={}", privacy);
+        }
+
+        // Only the callee is described: fall back to From
+        let identity = CallerIdentity::from_headers(
+            "\"Front Desk\" <sip:200@pbx>",
+            &headers(&[("Remote-Party-ID", "<sip:100@pbx>;party=called")]),
+        );
+        assert_eq!(identity.source, IdentitySource::From);
+        assert_eq!(identity.label(), "Front Desk");
+    }
+
+    #[test]
+    fn numbers_are_grouped_for_display() {
+        for (number, formatted) in [
+            ("+14155550100", "+1 (415) 555-0100"),
+            ("+442079460000", "+44 207 946 0000"),
+            ("+33123456789", "+33 123 456 789"),
+            ("+353861234567", "+353 861 234 567"),
+            ("+4412", "+4412"),
+            ("200", "200"),
+            ("+1-415", "+1-415"),
+        ] {
+            assert_eq!(format_number(number), formatted, "{}", number);
+        }
+        let identity = CallerIdentity::from_headers("<sip:4155550100@pbx>", &[]);
+        assert_eq!(identity.label(), "4155550100");
+        let identity = CallerIdentity::from_headers("<tel:+14155550100>", &[]);
+        assert_eq!(identity.label(), "+1 (415) 555-0100");
+    }
+}
@@ -0,0 +1,143 @@
+//! Codec preferences offered in SDP and the codec a call settled on.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Opus,
+    G722,
+    Pcmu,
+    Pcma,
+}
+
+impl Codec {
+    pub fn all() -> [Self; 4] {
+        [Self::Opus, Self::G722, Self::Pcmu, Self::Pcma]
+    }
+
+    /// SDP encoding name (RFC 3551 / RFC 7587).
+    pub fn sdp_name(self) -> &'static str {
+        match self {
+            Self::Opus => "opus",
+            Self::G722 => "G722",
+            Self::Pcmu => "PCMU",
+            Self::Pcma => "PCMA",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Opus => "Opus (wideband, adaptive)",
+            Self::G722 => "G.722 (16 kHz wideband)",
+            Self::Pcmu => "G.711 µ-law (PCMU)",
+            Self::Pcma => "G.711 A-law (PCMA)",
+        }
+    }
+}
+
+/// Opus encoder settings, sent as SDP `fmtp` parameters (RFC 7587 §6.1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpusSettings {
+    /// Average bitrate cap in kbit/s (6–510).
+    pub max_bitrate_kbps: u32,
+    /// In-band forward error correction.
+    pub fec: bool,
+    /// Discontinuous transmission during silence.
+    pub dtx: bool,
+}
+
+impl Default for OpusSettings {
+    fn default() -> Self {
+        Self {
+            max_bitrate_kbps: 32,
+            fec: true,
+            dtx: false,
+        }
+    }
+}
+
+impl OpusSettings {
+    pub fn fmtp(&self) -> String {
+        format!(
+            "maxaveragebitrate={};useinbandfec={};usedtx={}",
+            self.max_bitrate_kbps.clamp(6, 510) * 1000,
+            u8::from(self.fec),
+            u8::from(self.dtx)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecPreferences {
+    /// Codecs in offer order; disabled ones are kept so the UI remembers
+    /// their position.
+    pub order: Vec<(Codec, bool)>,
+    pub opus: OpusSettings,
+    /// Packetization time in ms (`a=ptime`).
+    pub ptime_ms: u32,
+}
+
+impl Default for CodecPreferences {
+    fn default() -> Self {
+        Self {
+            order: Codec::all().into_iter().map(|c| (c, true)).collect(),
+            opus: OpusSettings::default(),
+            ptime_ms: 20,
+        }
+    }
+}
+
+impl CodecPreferences {
+    /// Enabled codecs in preference order.
+    pub fn enabled(&self) -> Vec<Codec> {
+        self.order
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(codec, _)| *codec)
+            .collect()
+    }
+
+    /// Move the codec at `index` one place up (`up`) or down.
+    pub fn move_codec(&mut self, index: usize, up: bool) {
+        let other = if up { index.checked_sub(1) } else { Some(index + 1) };
+        if let Some(other) = other.filter(|&i| i < self.order.len()) {
+            self.order.swap(index, other);
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled().is_empty() {
+            return Err("Enable at least one codec".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The codec a call negotiated, as reported by the media layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegotiatedMedia {
+    pub codec: String,
+    pub payload_type: u8,
+    pub clock_rate: u32,
+    pub ptime_ms: u32,
+    /// SDES crypto suite when media is SRTP-encrypted.
+    pub srtp_suite: Option<String>,
+}
+
+impl NegotiatedMedia {
+    pub fn is_encrypted(&self) -> bool {
+        self.srtp_suite.is_some()
+    }
+}
+
+impl fmt::Display for NegotiatedMedia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} • {} kHz • {} ms",
+            self.codec,
+            self.clock_rate as f32 / 1000.0,
+            self.ptime_ms
+        )
+    }
+}
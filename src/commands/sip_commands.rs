@@ -1,4 +1,13 @@
-use crate::sip_client::CallInfo;
+use std::path::PathBuf;
+
+use crate::codecs::CodecPreferences;
+use crate::dial_plan::DialPlan;
+use crate::dial_policy::DialPolicy;
+use crate::playback::PlaybackMode;
+use crate::recording::RecordingPolicy;
+use crate::sip_client::{CallInfo, CallOptions, CaptureOptions, ExtraHeaders, KeepaliveConfig, ModeSelection, PrivacyConfig, ProxyConfig};
+use crate::srtp::SrtpPolicy;
+use crate::tones::RingConfig;
 
 /// Commands sent from UI to SIP coroutine
 #[derive(Debug, Clone)]
@@ -6,16 +15,42 @@ use crate::sip_client::CallInfo;
 pub enum SipCommand {
     /// Initialize the SIP client with configuration
     Initialize {
+        /// Connection mode; `Auto` infers it from `server_uri`
+        mode: ModeSelection,
         username: String,
         password: String,
         server_uri: String,
+        /// Digest auth ID; empty to authenticate as `username`
+        auth_username: String,
+        /// SIP domain; empty to use the server host
+        domain: String,
+        /// Digest realm; empty to accept the server's
+        realm: String,
+        /// From display name; empty for none
+        display_name: String,
         local_ip: Option<String>,
         local_port: u16,
+        keepalive: KeepaliveConfig,
+        proxy: ProxyConfig,
+        dial_plan: DialPlan,
+        dial_policy: DialPolicy,
+        privacy: PrivacyConfig,
+        extra_headers: ExtraHeaders,
+        codecs: CodecPreferences,
+        srtp: SrtpPolicy,
+        recording: RecordingPolicy,
+        /// Looped to the other party while we hold the call
+        music_on_hold: Option<PathBuf>,
+        ringing: RingConfig,
     },
     
     /// Make an outgoing call
     MakeCall {
         target: String,
+        /// Per-call options (caller-ID privacy, extra headers)
+        options: CallOptions,
+        /// Dialing-policy override PIN, when retrying a refused call
+        override_pin: Option<String>,
     },
     
     /// Answer an incoming call
@@ -26,6 +61,18 @@ pub enum SipCommand {
     
     /// Toggle mute state
     ToggleMute,
+
+    /// Start or stop recording the current call
+    ToggleRecording,
+
+    /// Play an audio file (WAV or Ogg Opus) into the current call
+    PlayFile {
+        path: PathBuf,
+        mode: PlaybackMode,
+    },
+
+    /// Stop the file being played into the call
+    StopPlayback,
     
     /// Put call on hold
     Hold,
@@ -36,6 +83,8 @@ pub enum SipCommand {
     /// Blind-transfer the call to another party (RFC 3515)
     Transfer {
         target: String,
+        /// Dialing-policy override PIN, when retrying a refused transfer
+        override_pin: Option<String>,
     },
 
     /// Send a DTMF digit on the active call (RFC 4733)
@@ -49,9 +98,26 @@ pub enum SipCommand {
         device_id: String,
     },
 
+    /// Change what calls capture for pcap export
+    SetCaptureOptions(CaptureOptions),
+
+    /// Write a call's SIP (and RTP, if captured) to `path` as pcapng
+    SaveCapture {
+        call_id: String,
+        path: PathBuf,
+    },
+
+    /// Write the redacted diagnostics bundle (config, logs, trace, history,
+    /// devices) to `path`
+    ExportDiagnostics {
+        path: PathBuf,
+    },
+
     /// Begin an attended transfer: place a consultation call to `target`
     StartAttendedTransfer {
         target: String,
+        /// Dialing-policy override PIN, when retrying a refused consultation
+        override_pin: Option<String>,
     },
 
     /// Complete the in-progress attended transfer (connect the two parties)
@@ -122,21 +188,26 @@ pub enum SipResponse {
 }
 
 /// Errors that can occur during SIP operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 #[allow(dead_code)]
 pub enum SipError {
     /// Client not initialized
+    #[error("client not initialized")]
     NotInitialized,
     
     /// No active call
+    #[error("no active call")]
     NoActiveCall,
     
     /// Operation failed
+    #[error("{0}")]
     OperationFailed(String),
     
-    /// Invalid parameters
+    /// Invalid parameters (including calls refused by the dialing policy)
+    #[error("{0}")]
     InvalidParameters(String),
     
     /// Network error
+    #[error("network error: {0}")]
     NetworkError(String),
 }
\ No newline at end of file
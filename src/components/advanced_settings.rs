@@ -0,0 +1,417 @@
+use dioxus::prelude::*;
+use std::time::Duration;
+
+use crate::dial_plan::{DialPlan, NumberFormat, RewriteRule};
+use crate::dial_policy::{self, DialPolicy, PolicyProfile};
+use crate::sip_client::{ExtraHeaders, KeepaliveConfig, KeepaliveMode, PrivacyConfig, ProxyConfig};
+use crate::sip_headers;
+
+/// Collapsible "Advanced" section of the registration form for settings most
+/// users never touch (digest realm, NAT keepalives, outbound proxy, dial
+/// plan, dialing policy, caller-ID privacy, extra headers, ...).
+#[component]
+pub fn AdvancedSettings(
+    mut realm: Signal<String>,
+    mut keepalive: Signal<KeepaliveConfig>,
+    mut proxy: Signal<ProxyConfig>,
+    mut dial_plan: Signal<DialPlan>,
+    mut dial_policy: Signal<DialPolicy>,
+    mut privacy: Signal<PrivacyConfig>,
+    mut extra_headers: Signal<ExtraHeaders>,
+    disabled: bool,
+) -> Element {
+    let mut expanded = use_signal(|| false);
+    // Raw rewrite-rule text, so half-typed rules are not dropped on each keystroke
+    let mut rules_text = use_signal(|| RewriteRule::format_list(&dial_plan.read().rules));
+    let mut allow_text = use_signal(|| dial_policy.read().allow.join("\n"));
+    let mut deny_text = use_signal(|| dial_policy.read().deny.join("\n"));
+    let mut invite_headers_text = use_signal(|| sip_headers::format_lines(&extra_headers.read().invite));
+    let mut register_headers_text = use_signal(|| sip_headers::format_lines(&extra_headers.read().register));
+
+    let mode_value = match keepalive.read().mode {
+        KeepaliveMode::Off => "off",
+        KeepaliveMode::Crlf => "crlf",
+        KeepaliveMode::Options => "options",
+    };
+    let interval_secs = keepalive.read().interval.as_secs();
+    let proxy_value = proxy.read().outbound_proxy.clone().unwrap_or_default();
+    let always_use_proxy = proxy.read().always_use_proxy;
+    let loose_route = proxy.read().loose_route;
+    let plan = dial_plan.read().clone();
+    let format_value = match plan.format {
+        NumberFormat::Sip => "sip",
+        NumberFormat::UserPhone => "user-phone",
+        NumberFormat::Tel => "tel",
+    };
+    let policy = dial_policy.read().clone();
+    let policy_error = policy.validate().err();
+    let override_pin = policy.override_pin.clone().unwrap_or_default();
+    let privacy_value = privacy.read().clone();
+    let preferred_identity = privacy_value.preferred_identity.clone().unwrap_or_default();
+    let headers_error = sip_headers::parse_lines(&invite_headers_text.read())
+        .and(sip_headers::parse_lines(&register_headers_text.read()))
+        .err();
+
+    rsx! {
+        div {
+            class: "border-t border-gray-200 pt-4",
+            button {
+                class: "text-sm font-medium text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                if *expanded.read() { "▾ Advanced" } else { "▸ Advanced" }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "flex flex-col gap-4 mt-4",
+
+                    // Digest realm
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Auth Realm (optional)"
+                        }
+                        input {
+                            class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: "Accept the server's realm",
+                            value: "{realm}",
+                            disabled: disabled,
+                            oninput: move |evt| realm.set(evt.value()),
+                        }
+                    }
+
+                    // NAT keepalive row
+                    div {
+                        class: "flex gap-3",
+                        div {
+                            class: "flex-[2]",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "NAT Keepalive"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                value: mode_value,
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let mode = match evt.value().as_str() {
+                                        "off" => KeepaliveMode::Off,
+                                        "options" => KeepaliveMode::Options,
+                                        _ => KeepaliveMode::Crlf,
+                                    };
+                                    keepalive.write().mode = mode;
+                                },
+                                option { value: "crlf", selected: mode_value == "crlf", "CRLF ping" }
+                                option { value: "options", selected: mode_value == "options", "OPTIONS (detects outages)" }
+                                option { value: "off", selected: mode_value == "off", "Off" }
+                            }
+                        }
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Interval (s)"
+                            }
+                            input {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "number",
+                                value: "{interval_secs}",
+                                min: "5",
+                                max: "3600",
+                                disabled: disabled || mode_value == "off",
+                                oninput: move |evt| {
+                                    // Clamped to >= 5s by the manager, so partial input is fine here.
+                                    if let Ok(secs) = evt.value().parse::<u64>() {
+                                        if secs > 0 {
+                                            keepalive.write().interval = Duration::from_secs(secs);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Outbound proxy
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Outbound Proxy (optional)"
+                        }
+                        input {
+                            class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: "sip:edge.carrier.net:5060",
+                            value: "{proxy_value}",
+                            disabled: disabled,
+                            oninput: move |evt| {
+                                let value = evt.value();
+                                proxy.write().outbound_proxy = if value.trim().is_empty() {
+                                    None
+                                } else {
+                                    Some(value.trim().to_string())
+                                };
+                            }
+                        }
+                        div {
+                            class: "flex gap-6 mt-2",
+                            label {
+                                class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: always_use_proxy,
+                                    disabled: disabled || proxy_value.is_empty(),
+                                    oninput: move |evt| proxy.write().always_use_proxy = evt.checked(),
+                                }
+                                "Always route via proxy"
+                            }
+                            label {
+                                class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: loose_route,
+                                    disabled: disabled || proxy_value.is_empty(),
+                                    oninput: move |evt| proxy.write().loose_route = evt.checked(),
+                                }
+                                "Loose routing (;lr)"
+                            }
+                        }
+                    }
+
+                    // Dial plan
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Dial Plan"
+                        }
+                        div {
+                            class: "flex gap-3",
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Outside line",
+                                title: "Outside-line prefix to strip, e.g. 9",
+                                value: "{plan.outside_line}",
+                                disabled: disabled,
+                                oninput: move |evt| dial_plan.write().outside_line = evt.value().trim().to_string(),
+                            }
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Intl. prefix",
+                                title: "International access code replaced by +, e.g. 00 or 011",
+                                value: "{plan.international_prefix}",
+                                disabled: disabled,
+                                oninput: move |evt| dial_plan.write().international_prefix = evt.value().trim().to_string(),
+                            }
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Trunk prefix",
+                                title: "National trunk prefix replaced by +country code, e.g. 0",
+                                value: "{plan.national_prefix}",
+                                disabled: disabled,
+                                oninput: move |evt| dial_plan.write().national_prefix = evt.value().trim().to_string(),
+                            }
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Country code",
+                                title: "Country calling code for national numbers, e.g. 44",
+                                value: "{plan.country_code}",
+                                disabled: disabled,
+                                oninput: move |evt| dial_plan.write().country_code = evt.value().trim().trim_start_matches('+').to_string(),
+                            }
+                        }
+                        div {
+                            class: "flex gap-3 mt-2",
+                            input {
+                                class: "flex-[2] min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Rewrite rules, e.g. 1800=+1800, 112=112",
+                                value: "{rules_text}",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let text = evt.value();
+                                    dial_plan.write().rules = RewriteRule::parse_list(&text);
+                                    rules_text.set(text);
+                                }
+                            }
+                            select {
+                                class: "flex-1 h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                value: format_value,
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    dial_plan.write().format = match evt.value().as_str() {
+                                        "sip" => NumberFormat::Sip,
+                                        "tel" => NumberFormat::Tel,
+                                        _ => NumberFormat::UserPhone,
+                                    };
+                                },
+                                option { value: "user-phone", selected: format_value == "user-phone", "sip: ;user=phone" }
+                                option { value: "tel", selected: format_value == "tel", "tel: URI" }
+                                option { value: "sip", selected: format_value == "sip", "Plain sip:" }
+                            }
+                        }
+                    }
+
+                    // Dialing policy
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Dialing Policy"
+                        }
+                        select {
+                            class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            disabled: disabled,
+                            oninput: move |evt| {
+                                let profile = PolicyProfile::all()
+                                    .into_iter()
+                                    .find(|p| p.label() == evt.value())
+                                    .unwrap_or_default();
+                                dial_policy.write().profile = profile;
+                            },
+                            for profile in PolicyProfile::all() {
+                                option {
+                                    value: profile.label(),
+                                    selected: policy.profile == profile,
+                                    "{profile.label()}"
+                                }
+                            }
+                        }
+                        div {
+                            class: "flex gap-3 mt-2",
+                            textarea {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm font-mono bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                rows: "3",
+                                placeholder: "Allow (one per line)\n+44\nre:^[0-9]{3,4}$",
+                                value: "{allow_text}",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let text = evt.value();
+                                    dial_policy.write().allow = dial_policy::parse_rules(&text);
+                                    allow_text.set(text);
+                                }
+                            }
+                            textarea {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm font-mono bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                rows: "3",
+                                placeholder: "Deny (one per line)\n+1900\nre:^\\+882",
+                                value: "{deny_text}",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let text = evt.value();
+                                    dial_policy.write().deny = dial_policy::parse_rules(&text);
+                                    deny_text.set(text);
+                                }
+                            }
+                        }
+                        if let Some(error) = policy_error {
+                            p { class: "mt-1 text-xs text-red-600", "{error}" }
+                        }
+                        input {
+                            class: "w-full mt-2 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "password",
+                            placeholder: "Override PIN (optional)",
+                            value: "{override_pin}",
+                            disabled: disabled,
+                            oninput: move |evt| {
+                                let pin = evt.value().trim().to_string();
+                                dial_policy.write().override_pin = if pin.is_empty() { None } else { Some(pin) };
+                            }
+                        }
+                    }
+
+                    // Caller-ID privacy
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Caller ID Privacy"
+                        }
+                        div {
+                            class: "flex gap-6",
+                            label {
+                                class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: privacy_value.hide_caller_id,
+                                    disabled: disabled,
+                                    oninput: move |evt| privacy.write().hide_caller_id = evt.checked(),
+                                }
+                                "Hide caller ID by default"
+                            }
+                            label {
+                                class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: privacy_value.privacy_header,
+                                    disabled: disabled,
+                                    oninput: move |evt| privacy.write().privacy_header = evt.checked(),
+                                }
+                                "Send Privacy: id"
+                            }
+                        }
+                        input {
+                            class: "w-full mt-2 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: "P-Preferred-Identity on hidden calls, e.g. sip:+14155550100@carrier.net",
+                            value: "{preferred_identity}",
+                            disabled: disabled,
+                            oninput: move |evt| {
+                                let value = evt.value().trim().to_string();
+                                privacy.write().preferred_identity = if value.is_empty() { None } else { Some(value) };
+                            }
+                        }
+                    }
+
+                    // Extra headers
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Extra Headers"
+                        }
+                        div {
+                            class: "flex gap-3",
+                            textarea {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm font-mono bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                rows: "3",
+                                placeholder: "INVITE (one per line)\nX-Account-Code: 4711",
+                                value: "{invite_headers_text}",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let text = evt.value();
+                                    // Keep the last valid list while the text is being edited
+                                    if let Ok(headers) = sip_headers::parse_lines(&text) {
+                                        extra_headers.write().invite = headers;
+                                    }
+                                    invite_headers_text.set(text);
+                                }
+                            }
+                            textarea {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm font-mono bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                rows: "3",
+                                placeholder: "REGISTER (one per line)\nX-Device: lab-3",
+                                value: "{register_headers_text}",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let text = evt.value();
+                                    if let Ok(headers) = sip_headers::parse_lines(&text) {
+                                        extra_headers.write().register = headers;
+                                    }
+                                    register_headers_text.set(text);
+                                }
+                            }
+                        }
+                        if let Some(error) = headers_error {
+                            p { class: "mt-1 text-xs text-red-600", "{error}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
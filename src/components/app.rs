@@ -1,17 +1,36 @@
 use dioxus::prelude::*;
-use log::{error, info};
+use log::{error, info, warn};
 use futures_util::StreamExt;
-use crate::sip_client::{CallInfo, CallState, SipClientManager, SipConfig, ConnectionMode};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::sip_client::{CallInfo, CallState, SipClientManager, SipConfig, ConnectionMode, CallOptions, CaptureOptions, ExtraHeaders, KeepaliveConfig, ModeSelection, PrivacyConfig, ProxyConfig};
+use crate::call_history::{CallHistory, CallOutcome};
+use crate::call_stats::{QualityMonitor, QualityThresholds};
+use crate::caller_id::CallerIdentity;
+use crate::codecs::CodecPreferences;
+use crate::srtp::SrtpPolicy;
+use crate::playback::PlaybackProgress;
+use crate::recording::RecordingPolicy;
+use crate::tones::RingConfig;
 use crate::commands::SipCommand;
+use crate::commands::sip_commands::SipError;
+use crate::dial_plan::DialPlan;
+use crate::dial_policy::DialPolicy;
 use super::{RegistrationScreen, CallInterfaceScreen, IncomingCallScreen};
+use super::dial_policy_notice::{PolicyRefusal, RefusedAction};
 use crate::event_channel::SipEvent;
+use crate::pcap::{self, CapturedPacket};
+use crate::diagnostics;
+use crate::logging::{self, CallDirection};
+use crate::sip_trace::SipTrace;
 use tokio::sync::mpsc;
 
 #[derive(Clone, Debug, PartialEq)]
 enum AppState {
     Registration,
     CallInterface,
-    IncomingCall { caller_id: String },
+    IncomingCall { caller: CallerIdentity },
 }
 
 #[allow(non_snake_case)]
@@ -24,11 +43,25 @@ pub fn App() -> Element {
     let is_on_hook = use_signal(|| true);  // Track hook state in UI
     let audio_levels = use_signal(|| (0.0f32, 0.0f32)); // (input, output) VU levels
     let transfer_in_progress = use_signal(|| false); // attended transfer consultation active
+    let registrar_reachable = use_signal(|| true); // cleared when OPTIONS keepalives go unanswered
+    let policy_refusal = use_signal(|| None::<PolicyRefusal>); // last call refused by the dialing policy
+    let call_history = use_signal(CallHistory::default);
+    let sip_trace = use_signal(SipTrace::default); // recent raw SIP messages for the trace viewer
+    let capture_options = use_signal(CaptureOptions::default);
+    let capture_notice = use_signal(|| None::<String>); // result of the last manual pcap save
+    let diagnostics_notice = use_signal(|| None::<String>); // result of the last diagnostics export
+    let playback = use_signal(|| None::<(String, PlaybackProgress)>); // file being played into the call
+    let playback_notice = use_signal(|| None::<String>); // why the last file could not be played
     
     // Form fields
     let username = use_signal(|| "".to_string());
     let password = use_signal(|| "".to_string());
     let server_uri = use_signal(|| "".to_string());
+    let connection_mode = use_signal(ModeSelection::default);
+    let auth_username = use_signal(|| "".to_string());
+    let sip_domain = use_signal(|| "".to_string());
+    let realm = use_signal(|| "".to_string());
+    let display_name = use_signal(|| "".to_string());
     let call_target = use_signal(|| "".to_string());
     let selected_interface = use_signal(|| {
         // Initialize with the first available interface
@@ -40,6 +73,18 @@ pub fn App() -> Element {
         }
     });
     let port = use_signal(|| "5060".to_string());
+    let keepalive = use_signal(KeepaliveConfig::default);
+    let proxy = use_signal(ProxyConfig::default);
+    let dial_plan = use_signal(DialPlan::default);
+    let dial_policy = use_signal(DialPolicy::default);
+    let privacy = use_signal(PrivacyConfig::default);
+    let extra_headers = use_signal(ExtraHeaders::default);
+    let codecs = use_signal(CodecPreferences::default);
+    let srtp = use_signal(SrtpPolicy::default);
+    let recording = use_signal(RecordingPolicy::default);
+    let music_on_hold = use_signal(|| None::<PathBuf>); // looped to the other party while we hold
+    let ringing = use_signal(RingConfig::default);
+    let call_options = use_signal(CallOptions::default); // options for the next call, reset after dialing
     
     // Create the SIP coroutine that owns the SipClientManager
     // This coroutine processes commands and manages all SIP state
@@ -51,6 +96,14 @@ pub fn App() -> Element {
         let mut app_state = app_state.clone();
         let mut audio_levels = audio_levels.clone();
         let mut transfer_in_progress = transfer_in_progress.clone();
+        let mut registrar_reachable = registrar_reachable.clone();
+        let mut policy_refusal = policy_refusal.clone();
+        let mut call_history = call_history.clone();
+        let mut sip_trace = sip_trace.clone();
+        let mut capture_notice = capture_notice.clone();
+        let mut diagnostics_notice = diagnostics_notice.clone();
+        let mut playback = playback.clone();
+        let mut playback_notice = playback_notice.clone();
 
         move |mut rx: UnboundedReceiver<SipCommand>| async move {
             // The coroutine owns the SipClientManager
@@ -59,6 +112,8 @@ pub fn App() -> Element {
             // Attended transfer in progress: (held original call, consult id, target).
             let mut attended: Option<(CallInfo, String, String)> = None;
             let mut hook_state = true; // Start on-hook
+            // Degradation tracking per call (consultation legs included)
+            let mut quality_monitors: HashMap<String, QualityMonitor> = HashMap::new();
             
             // Create event channel for this coroutine
             let (event_sender, mut event_receiver) = mpsc::unbounded_channel::<SipEvent>();
@@ -71,20 +126,40 @@ pub fn App() -> Element {
                 info!("SIP Coroutine: Processing command {:?}", command);
                 
                 match command {
-                    SipCommand::Initialize { username, password, server_uri, local_ip, local_port } => {
+                    SipCommand::Initialize { mode, username, password, server_uri, auth_username, domain, realm, display_name, local_ip, local_port, keepalive, proxy, dial_plan, dial_policy, privacy, extra_headers, codecs, srtp, recording, music_on_hold, ringing } => {
+                        // Optional form fields arrive as possibly-blank strings
+                        let non_empty = |value: String| {
+                            let value = value.trim().to_string();
+                            if value.is_empty() { None } else { Some(value) }
+                        };
+
+                        let valid = mode
+                            .validate(&username, &server_uri)
+                            .and_then(|_| dial_policy.validate())
+                            .and_then(|_| codecs.validate());
+                        if let Err(reason) = valid {
+                            error!("Invalid {} settings: {}", mode.resolve(&server_uri).label(), reason);
+                            registration_state.set(CallState::Error(reason.clone()));
+                            error_message.set(Some(reason));
+                            continue;
+                        }
+
                         // Update configuration
-                        let connection_mode = if server_uri.is_empty() {
-                            ConnectionMode::Receiver
-                        } else if server_uri.contains('@') {
-                            ConnectionMode::PeerToPeer {
+                        let mode = mode.resolve(&server_uri);
+                        let connection_mode = match mode {
+                            ModeSelection::Receiver => ConnectionMode::Receiver,
+                            ModeSelection::PeerToPeer => ConnectionMode::PeerToPeer {
                                 target_uri: server_uri.clone(),
-                            }
-                        } else {
-                            ConnectionMode::Server {
+                            },
+                            ModeSelection::Server | ModeSelection::Auto => ConnectionMode::Server {
                                 server_uri: server_uri.clone(),
                                 username: username.clone(),
                                 password: password.clone(),
-                            }
+                                auth_username: non_empty(auth_username),
+                                domain: non_empty(domain),
+                                realm: non_empty(realm),
+                                display_name: non_empty(display_name),
+                            },
                         };
                         
                         let config = SipConfig {
@@ -92,12 +167,30 @@ pub fn App() -> Element {
                             connection_mode,
                             local_port,
                             local_ip,
+                            keepalive,
+                            proxy,
+                            dial_plan,
+                            dial_policy,
+                            privacy,
+                            extra_headers,
+                            codecs,
+                            srtp,
+                            recording,
+                            music_on_hold,
+                            ringing,
                         };
                         
                         sip_client.update_config(config);
 
+                        // Tag the log with the account this session runs as
+                        logging::set_account(Some(match sip_client.sip_domain() {
+                            Some(domain) => format!("{}@{}", username, domain),
+                            None => username.clone(),
+                        }));
+                        registrar_reachable.set(true);
+
                         // Server mode registers; P2P/Receiver do not.
-                        let is_server_mode = !server_uri.is_empty() && !server_uri.contains('@');
+                        let is_server_mode = mode == ModeSelection::Server;
 
                         // Initialize the client
                         match sip_client.initialize().await {
@@ -135,13 +228,20 @@ pub fn App() -> Element {
                         }
                     }
                     
-                    SipCommand::MakeCall { target } => {
-                        match sip_client.make_call(&target).await {
+                    SipCommand::MakeCall { target, options, override_pin } => {
+                        match sip_client.make_call(&target, &options, override_pin.as_deref()).await {
                             Ok(call_id) => {
+                                policy_refusal.set(None);
                                 info!("Call initiated with ID: {}", call_id);
                                 // Create call info for outgoing call
                                 let call_info = CallInfo {
                                     id: call_id.clone(),
+                                    identity: CallerIdentity::from_target(&target),
+                                    media: None,
+                                    stats: None,
+                                    quality_warning: None,
+                                    recording: None,
+                                    recording_refused: None,
                                     remote_uri: target,
                                     state: CallState::Calling,
                                     duration: None,
@@ -155,6 +255,15 @@ pub fn App() -> Element {
                             Err(e) => {
                                 error!("Failed to make call: {}", e);
                                 error_message.set(Some(format!("Failed to make call: {}", e)));
+                                if let Some(reason) = refusal_reason(&e) {
+                                    policy_refusal.set(Some(PolicyRefusal {
+                                        target,
+                                        reason,
+                                        action: RefusedAction::Call,
+                                        options,
+                                        can_override: sip_client.get_config().dial_policy.override_pin.is_some(),
+                                    }));
+                                }
                             }
                         }
                     }
@@ -164,6 +273,9 @@ pub fn App() -> Element {
                             match sip_client.hangup(&call_info.id).await {
                                 Ok(_) => {
                                     info!("Call ended");
+                                    // hangup() stopped the audio, which finished any recording
+                                    let recordings = sip_client.take_recordings(&call_info.id);
+                                    call_history.write().record(call_info, CallOutcome::ended(call_info, true)).recordings = recordings;
                                     current_call_info = None;
                                     current_call.set(None);
                                 }
@@ -209,6 +321,42 @@ pub fn App() -> Element {
                         }
                     }
                     
+                    SipCommand::ToggleRecording => {
+                        if let Some(call_info) = current_call_info.as_mut() {
+                            if call_info.recording.is_some() {
+                                sip_client.stop_recording();
+                                call_info.recording = None;
+                            } else {
+                                match sip_client.start_recording(call_info) {
+                                    Ok(path) => {
+                                        call_info.recording = Some(path);
+                                        call_info.recording_refused = None;
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to start recording: {}", e);
+                                        error_message.set(Some(format!("Could not start recording: {}", e)));
+                                        call_info.recording_refused = Some(e.to_string());
+                                    }
+                                }
+                            }
+                            current_call.set(Some(call_info.clone()));
+                        }
+                    }
+
+                    SipCommand::PlayFile { path, mode } => {
+                        match sip_client.play_file(&path, mode) {
+                            Ok(()) => playback_notice.set(None),
+                            Err(e) => {
+                                error!("Failed to play file: {}", e);
+                                playback_notice.set(Some(e.to_string()));
+                            }
+                        }
+                    }
+
+                    SipCommand::StopPlayback => {
+                        sip_client.stop_playback();
+                    }
+
                     SipCommand::Hold => {
                         if let Some(call_info) = &current_call_info {
                             match sip_client.hold(&call_info.id).await {
@@ -253,17 +401,19 @@ pub fn App() -> Element {
                         if !hook_state && current_call_info.as_ref().map(|c| c.is_incoming && c.state == CallState::Ringing).unwrap_or(false) {
                             if let Some(call_info) = &current_call_info {
                                 let _ = sip_client.hangup(&call_info.id).await;
+                                call_history.write().record(call_info, CallOutcome::Declined);
                                 current_call_info = None;
                                 current_call.set(None);
                             }
                         }
                     }
                     
-                    SipCommand::Transfer { target } => {
+                    SipCommand::Transfer { target, override_pin } => {
                         let id = current_call_info.as_ref().map(|c| c.id.clone());
                         match id {
-                            Some(id) => match sip_client.transfer(&id, &target).await {
+                            Some(id) => match sip_client.transfer(&id, &target, override_pin.as_deref()).await {
                                 Ok(_) => {
+                                    policy_refusal.set(None);
                                     info!("Blind transfer to {} initiated", target);
                                     if let Some(ref mut ci) = current_call_info {
                                         ci.state = CallState::Transferring;
@@ -273,6 +423,15 @@ pub fn App() -> Element {
                                 Err(e) => {
                                     error!("Failed to transfer call: {}", e);
                                     error_message.set(Some(format!("Failed to transfer: {}", e)));
+                                    if let Some(reason) = refusal_reason(&e) {
+                                        policy_refusal.set(Some(PolicyRefusal {
+                                            target,
+                                            reason,
+                                            action: RefusedAction::Transfer,
+                                            options: CallOptions::default(),
+                                            can_override: sip_client.get_config().dial_policy.override_pin.is_some(),
+                                        }));
+                                    }
                                 }
                             },
                             None => {
@@ -291,16 +450,23 @@ pub fn App() -> Element {
                         }
                     }
 
-                    SipCommand::StartAttendedTransfer { target } => {
+                    SipCommand::StartAttendedTransfer { target, override_pin } => {
                         let original = current_call_info.clone();
                         match original {
                             Some(orig_ci) => {
-                                match sip_client.start_consultation(&orig_ci.id, &target).await {
+                                match sip_client.start_consultation(&orig_ci.id, &target, override_pin.as_deref()).await {
                                     Ok(consult_id) => {
+                                        policy_refusal.set(None);
                                         info!("Consultation call {} placed to {}", consult_id, target);
                                         attended = Some((orig_ci, consult_id.clone(), target.clone()));
                                         let ci = CallInfo {
                                             id: consult_id,
+                                            identity: CallerIdentity::from_target(&target),
+                                            media: None,
+                                            stats: None,
+                                            quality_warning: None,
+                                            recording: None,
+                                            recording_refused: None,
                                             remote_uri: target,
                                             state: CallState::Calling,
                                             duration: None,
@@ -315,6 +481,15 @@ pub fn App() -> Element {
                                     Err(e) => {
                                         error!("Attended transfer start failed: {}", e);
                                         error_message.set(Some(format!("Attended transfer failed: {}", e)));
+                                        if let Some(reason) = refusal_reason(&e) {
+                                            policy_refusal.set(Some(PolicyRefusal {
+                                                target,
+                                                reason,
+                                                action: RefusedAction::Consultation,
+                                                options: CallOptions::default(),
+                                                can_override: sip_client.get_config().dial_policy.override_pin.is_some(),
+                                            }));
+                                        }
                                     }
                                 }
                             }
@@ -330,6 +505,11 @@ pub fn App() -> Element {
                             {
                                 Ok(_) => {
                                     info!("Attended transfer completed");
+                                    // The transfer stopped the audio, which finished any
+                                    // recording; the consultation has no entry of its own
+                                    let mut recordings = sip_client.take_recordings(&orig_ci.id);
+                                    recordings.extend(sip_client.take_recordings(&consult));
+                                    call_history.write().record(&orig_ci, CallOutcome::Transferred).recordings = recordings;
                                     current_call_info = None;
                                     current_call.set(None);
                                     transfer_in_progress.set(false);
@@ -358,6 +538,41 @@ pub fn App() -> Element {
                         }
                     }
 
+                    SipCommand::SetCaptureOptions(options) => {
+                        sip_client.set_capture_options(options);
+                    }
+
+                    SipCommand::SaveCapture { call_id, path } => {
+                        let packets = capture_packets(&sip_trace.read(), &sip_client, &call_id);
+                        let notice = match write_capture(&path, &packets, &call_id).await {
+                            Ok(()) => {
+                                info!("Saved capture of call {} ({} packets) to {}", call_id, packets.len(), path.display());
+                                format!("Saved {} packets to {}", packets.len(), path.display())
+                            }
+                            Err(e) => {
+                                error!("Failed to save capture of call {}: {}", call_id, e);
+                                format!("Could not save capture: {}", e)
+                            }
+                        };
+                        capture_notice.set(Some(notice));
+                    }
+
+                    SipCommand::ExportDiagnostics { path } => {
+                        let files = diagnostics::collect(sip_client.get_config(), &sip_trace.read(), &call_history.read());
+                        let bundle = diagnostics::bundle(&files, chrono::Local::now());
+                        let notice = match tokio::fs::write(&path, bundle).await {
+                            Ok(()) => {
+                                info!("Exported diagnostics to {}", path.display());
+                                format!("Saved to {}", path.display())
+                            }
+                            Err(e) => {
+                                error!("Failed to export diagnostics: {}", e);
+                                format!("Could not export diagnostics: {}", e)
+                            }
+                        };
+                        diagnostics_notice.set(Some(notice));
+                    }
+
                     SipCommand::SetAudioDevice { is_input, device_id } => {
                         let direction = if is_input {
                             crate::audio::AudioDirection::Input
@@ -375,36 +590,81 @@ pub fn App() -> Element {
                     
                     // Process events from SIP client
                     Some(event) = event_receiver.recv() => {
-                        info!("Coroutine: Processing event {:?}", event);
+                        // Tag the event's records with the call it is about,
+                        // which may be a consultation leg or a second incoming
+                        // call rather than the current one
+                        if let Some(id) = event.call_id() {
+                            let incoming = matches!(event, SipEvent::IncomingCall { .. })
+                                || current_call_info
+                                    .iter()
+                                    .chain(attended.as_ref().map(|(original, _, _)| original))
+                                    .any(|call| call.id == id && call.is_incoming);
+                            let direction = if incoming { CallDirection::Inbound } else { CallDirection::Outbound };
+                            logging::set_call(Some((id.to_string(), direction)));
+                        }
+
+                        // Raw messages go to the trace buffer, not the log
+                        if !matches!(event, SipEvent::Trace(_)) {
+                            info!("Coroutine: Processing event {:?}", event);
+                        }
 
                         match event {
-                            SipEvent::IncomingCall { call_id, from, .. } => {
-                                // Check if we're on hook (able to receive calls)
-                                if hook_state {
-                                    let call_info = CallInfo {
-                                        id: call_id.clone(),
-                                        remote_uri: from.clone(),
-                                        state: CallState::Ringing,
-                                        duration: None,
-                                        is_incoming: true,
-                                        connected_at: None,
-                                        is_muted: Some(false),
-                                    };
+                            SipEvent::IncomingCall { call_id, from, caller, srtp_offered } => {
+                                let call_info = CallInfo {
+                                    id: call_id.clone(),
+                                    remote_uri: from,
+                                    state: CallState::Ringing,
+                                    duration: None,
+                                    is_incoming: true,
+                                    connected_at: None,
+                                    is_muted: Some(false),
+                                    identity: caller.clone(),
+                                    media: None,
+                                    stats: None,
+                                    quality_warning: None,
+                                    recording: None,
+                                    recording_refused: None,
+                                };
+                                let srtp_violation = srtp_offered
+                                    .and_then(|offered| sip_client.get_config().srtp.violation(offered));
+                                if let Some(reason) = srtp_violation {
+                                    info!("Rejecting incoming call {}: {}", call_id, reason);
+                                    let _ = sip_client.reject_unencrypted_call(&call_id).await;
+                                    call_history.write().record(&call_info, CallOutcome::Unencrypted);
+                                } else if hook_state {
+                                    // On hook: able to receive calls
+                                    sip_client.start_ringtone();
                                     current_call_info = Some(call_info.clone());
                                     current_call.set(Some(call_info));
-                                    app_state.set(AppState::IncomingCall { caller_id: from.clone() });
+                                    app_state.set(AppState::IncomingCall { caller });
                                 } else {
                                     // We're off hook, reject the incoming call
                                     info!("Rejecting incoming call - phone is off hook");
                                     let _ = sip_client.reject_call(&call_id).await;
+                                    call_history.write().record(&call_info, CallOutcome::Missed);
                                 }
                             }
 
                             SipEvent::Ringing { call_id } => {
                                 if let Some(ref mut call_info) = current_call_info {
-                                    if call_info.id == call_id {
+                                    // A 180 after early media leaves the network's audio playing
+                                    if call_info.id == call_id && call_info.state != CallState::EarlyMedia {
                                         call_info.state = CallState::Ringing;
                                         current_call.set(Some(call_info.clone()));
+                                        if !call_info.is_incoming {
+                                            sip_client.start_ringback();
+                                        }
+                                    }
+                                }
+                            }
+
+                            SipEvent::EarlyMedia { call_id } => {
+                                if let Some(call_info) = current_call_info.as_mut().filter(|c| c.id == call_id && !c.is_incoming) {
+                                    call_info.state = CallState::EarlyMedia;
+                                    current_call.set(Some(call_info.clone()));
+                                    // Replaces the local ringback, if it was playing
+                                    if let Err(e) = sip_client.start_early_media(&call_id).await {
+                                        error!("Failed to play early media: {}", e);
                                     }
                                 }
                             }
@@ -423,18 +683,140 @@ pub fn App() -> Element {
                                 if let Err(e) = sip_client.start_audio(&call_id).await {
                                     error!("start_audio failed: {}", e);
                                 }
+                                if sip_client.get_config().recording.always_record {
+                                    if let Some(call_info) = current_call_info.as_mut().filter(|c| c.id == call_id && c.recording.is_none()) {
+                                        match sip_client.start_recording(call_info) {
+                                            Ok(path) => call_info.recording = Some(path),
+                                            Err(e) => {
+                                                error!("Failed to start recording: {}", e);
+                                                call_info.recording_refused = Some(e.to_string());
+                                            }
+                                        }
+                                        current_call.set(Some(call_info.clone()));
+                                    }
+                                }
+                            }
+
+                            SipEvent::MediaNegotiated { call_id, media } => {
+                                info!("Call {} media: {}", call_id, media);
+                                let srtp_violation = sip_client.get_config().srtp.violation(media.is_encrypted());
+                                if let Some(call_info) = current_call_info.as_mut().filter(|c| c.id == call_id) {
+                                    call_info.media = Some(media);
+                                    if let Some(reason) = srtp_violation {
+                                        // Safety net for peers that answered without keys
+                                        // (or an offerless INVITE we could not check up front)
+                                        error!("Hanging up call {}: {}", call_id, reason);
+                                        if let Err(e) = sip_client.hangup(&call_id).await {
+                                            error!("Failed to hang up unencrypted call: {}", e);
+                                        }
+                                        call_history.write().record(call_info, CallOutcome::Unencrypted);
+                                        policy_refusal.set(Some(PolicyRefusal {
+                                            target: call_info.remote_uri.clone(),
+                                            reason: reason.to_string(),
+                                            action: RefusedAction::Call,
+                                            options: CallOptions::default(),
+                                            can_override: false,
+                                        }));
+                                        current_call_info = None;
+                                        current_call.set(None);
+                                    } else {
+                                        current_call.set(Some(call_info.clone()));
+                                    }
+                                }
+                            }
+
+                            SipEvent::MediaStats { call_id, mut stats } => {
+                                let is_current = current_call_info.as_ref().is_some_and(|c| c.id == call_id);
+                                if is_current {
+                                    // The bridge only runs for the current call
+                                    stats.jitter_buffer_ms = sip_client.jitter_buffer_ms();
+                                }
+                                let warning = quality_monitors
+                                    .entry(call_id.clone())
+                                    .or_insert_with(|| QualityMonitor::new(QualityThresholds::default()))
+                                    .update(&stats, Instant::now());
+                                if let Some(call_info) = current_call_info.as_mut().filter(|_| is_current) {
+                                    if warning != call_info.quality_warning {
+                                        match &warning {
+                                            Some(message) => warn!("Call {}: {}", call_id, message),
+                                            None => info!("Call {}: connection quality recovered", call_id),
+                                        }
+                                    }
+                                    stats.codec = call_info.media.as_ref().map(|m| m.codec.clone());
+                                    call_info.stats = Some(stats);
+                                    call_info.quality_warning = warning;
+                                    current_call.set(Some(call_info.clone()));
+                                }
                             }
 
                             SipEvent::Ended { call_id, .. } => {
-                                if current_call_info.as_ref().map(|c| c.id == call_id).unwrap_or(false) {
+                                log_quality_summary(&mut quality_monitors, &call_id);
+                                sip_client.discard_capture(&call_id);
+                                // A held original or rejected second call ending
+                                // leaves the current call's audio and recording alone
+                                let current = current_call_info.as_ref().is_some_and(|c| c.id == call_id);
+                                if current {
+                                    sip_client.stop_recording();
+                                }
+                                let recordings = sip_client.take_recordings(&call_id);
+                                if let Some(call_info) = current_call_info.as_ref().filter(|_| current) {
+                                    call_history.write().record(call_info, CallOutcome::ended(call_info, false)).recordings = recordings;
                                     current_call_info = None;
                                     current_call.set(None);
+                                    sip_client.stop_audio();
                                 }
-                                sip_client.stop_audio();
                             }
 
                             SipEvent::Failed { call_id, code, reason } => {
-                                if current_call_info.as_ref().map(|c| c.id == call_id).unwrap_or(false) {
+                                log_quality_summary(&mut quality_monitors, &call_id);
+                                // Keep the evidence before the call's RTP capture is dropped
+                                let capture = if sip_client.capture_options().save_failed_calls {
+                                    let path = std::env::temp_dir()
+                                        .join("sip-client-captures")
+                                        .join(format!("call-{}-{}.pcapng", chrono::Local::now().format("%Y%m%d-%H%M%S"), code));
+                                    let packets = capture_packets(&sip_trace.read(), &sip_client, &call_id);
+                                    match write_capture(&path, &packets, &call_id).await {
+                                        Ok(()) => {
+                                            info!("Saved capture of failed call {} to {}", call_id, path.display());
+                                            Some(path)
+                                        }
+                                        Err(e) => {
+                                            error!("Failed to save capture of call {}: {}", call_id, e);
+                                            None
+                                        }
+                                    }
+                                } else {
+                                    None
+                                };
+                                sip_client.discard_capture(&call_id);
+                                if let Some(call_info) = current_call_info.as_ref().filter(|c| c.id == call_id) {
+                                    sip_client.stop_recording();
+                                    let recordings = sip_client.take_recordings(&call_id);
+                                    // 488 to our RTP/SAVP-only offer: the callee cannot do SRTP
+                                    let srtp_violation = sip_client
+                                        .get_config()
+                                        .srtp
+                                        .violation(false)
+                                        .filter(|_| code == 488 && !call_info.is_incoming);
+                                    let outcome = match srtp_violation {
+                                        Some(_) => CallOutcome::Unencrypted,
+                                        None => CallOutcome::Failed(code),
+                                    };
+                                    {
+                                        let mut history = call_history.write();
+                                        let entry = history.record(call_info, outcome);
+                                        entry.capture = capture;
+                                        entry.recordings = recordings;
+                                    }
+                                    if let Some(reason) = srtp_violation {
+                                        policy_refusal.set(Some(PolicyRefusal {
+                                            target: call_info.remote_uri.clone(),
+                                            reason: reason.to_string(),
+                                            action: RefusedAction::Call,
+                                            options: CallOptions::default(),
+                                            can_override: false,
+                                        }));
+                                    }
                                     current_call_info = None;
                                     current_call.set(None);
                                 }
@@ -442,7 +824,10 @@ pub fn App() -> Element {
                                 error_message.set(Some(format!("Call failed ({}): {}", code, reason)));
                             }
 
-                            SipEvent::OnHold { call_id } => {
+                            SipEvent::OnHold { call_id, remote } => {
+                                if remote && current_call_info.as_ref().is_some_and(|c| c.id == call_id) {
+                                    sip_client.set_remote_hold(true);
+                                }
                                 if let Some(ref mut call_info) = current_call_info {
                                     if call_info.id == call_id {
                                         call_info.state = CallState::OnHold;
@@ -451,7 +836,10 @@ pub fn App() -> Element {
                                 }
                             }
 
-                            SipEvent::Resumed { call_id } => {
+                            SipEvent::Resumed { call_id, remote } => {
+                                if remote && current_call_info.as_ref().is_some_and(|c| c.id == call_id) {
+                                    sip_client.set_remote_hold(false);
+                                }
                                 if let Some(ref mut call_info) = current_call_info {
                                     if call_info.id == call_id {
                                         call_info.state = CallState::Connected;
@@ -469,20 +857,37 @@ pub fn App() -> Element {
                                 }
                             }
 
-                            SipEvent::Registered { registrar } => {
+                            SipEvent::Registered { registrar, service_route } => {
                                 info!("Registered to {}", registrar);
+                                sip_client.set_service_route(service_route);
                                 registration_state.set(CallState::Registered);
                                 error_message.set(None);
                                 // Registration confirmed — now show the call UI.
                                 app_state.set(AppState::CallInterface);
                             }
 
-                            SipEvent::RegistrationFailed { registrar, reason } => {
+                            SipEvent::RegistrationFailed { registrar, code, reason } => {
                                 error!("Registration failed ({}): {}", registrar, reason);
-                                registration_state.set(CallState::Error(reason.clone()));
-                                error_message.set(Some(format!("Registration failed: {}", reason)));
-                                // Stay on the registration screen so creds can be corrected.
-                                app_state.set(AppState::Registration);
+                                // RFC 3263: a timeout or 503 moves on to the next DNS target.
+                                let failed_over = crate::sip_resolver::is_failover_status(code)
+                                    && sip_client.fail_over_registration().await;
+                                if failed_over {
+                                    info!("Retrying registration on the next registrar target");
+                                } else {
+                                    registration_state.set(CallState::Error(reason.clone()));
+                                    error_message.set(Some(format!("Registration failed: {}", reason)));
+                                    // Stay on the registration screen so creds can be corrected.
+                                    app_state.set(AppState::Registration);
+                                }
+                            }
+
+                            SipEvent::RegistrarReachability { registrar, reachable } => {
+                                if reachable {
+                                    info!("Registrar {} reachable again", registrar);
+                                } else {
+                                    error!("Registrar {} not answering keepalives", registrar);
+                                }
+                                registrar_reachable.set(reachable);
                             }
 
                             SipEvent::Error { message } => {
@@ -494,8 +899,19 @@ pub fn App() -> Element {
                                 info!("REFER received on {} -> {}", call_id, refer_to);
                                 match sip_client.follow_refer(&call_id, &refer_to).await {
                                     Ok(new_id) => {
+                                        if let Some(original) = current_call_info.as_ref().filter(|c| c.id == call_id) {
+                                            // follow_refer() stopped the audio, which finished any recording
+                                            let recordings = sip_client.take_recordings(&call_id);
+                                            call_history.write().record(original, CallOutcome::Transferred).recordings = recordings;
+                                        }
                                         let ci = CallInfo {
                                             id: new_id,
+                                            identity: CallerIdentity::from_target(&refer_to),
+                                            media: None,
+                                            stats: None,
+                                            quality_warning: None,
+                                            recording: None,
+                                            recording_refused: None,
                                             remote_uri: refer_to.clone(),
                                             state: CallState::Calling,
                                             duration: None,
@@ -531,6 +947,18 @@ pub fn App() -> Element {
                                 }
                             }
 
+                            SipEvent::Trace(message) => {
+                                sip_trace.write().push(message);
+                            }
+
+                            SipEvent::Playback { name, progress } => {
+                                if progress.is_none() {
+                                    // Hold music may have been waiting for it
+                                    sip_client.update_music_on_hold();
+                                }
+                                playback.set(progress.map(|progress| (name, progress)));
+                            }
+
                             SipEvent::AudioLevel { direction, level } => {
                                 let mut levels = *audio_levels.read();
                                 match direction {
@@ -546,6 +974,13 @@ pub fn App() -> Element {
                         }
                     }
                 }
+
+                // Until the next command or event, records (rvoip, audio
+                // threads) belong to whichever call is now current
+                logging::set_call(current_call_info.as_ref().map(|call| {
+                    let direction = if call.is_incoming { CallDirection::Inbound } else { CallDirection::Outbound };
+                    (call.id.clone(), direction)
+                }));
             }
         }
     });
@@ -559,8 +994,24 @@ pub fn App() -> Element {
         let username = username.clone();
         let password = password.clone();
         let server_uri = server_uri.clone();
+        let connection_mode = connection_mode.clone();
+        let auth_username = auth_username.clone();
+        let sip_domain = sip_domain.clone();
+        let realm = realm.clone();
+        let display_name = display_name.clone();
         let selected_interface = selected_interface.clone();
         let port = port.clone();
+        let keepalive = keepalive.clone();
+        let proxy = proxy.clone();
+        let dial_plan = dial_plan.clone();
+        let dial_policy = dial_policy.clone();
+        let privacy = privacy.clone();
+        let extra_headers = extra_headers.clone();
+        let codecs = codecs.clone();
+        let srtp = srtp.clone();
+        let recording = recording.clone();
+        let music_on_hold = music_on_hold.clone();
+        let ringing = ringing.clone();
         
         move |_| {
             info!("Starting connection process...");
@@ -574,11 +1025,27 @@ pub fn App() -> Element {
             
             // Send initialize command to coroutine
             sip_coroutine.send(SipCommand::Initialize {
+                mode: *connection_mode.read(),
                 username: username_val,
                 password: password_val,
                 server_uri: server_uri_val,
+                auth_username: auth_username.read().clone(),
+                domain: sip_domain.read().clone(),
+                realm: realm.read().clone(),
+                display_name: display_name.read().clone(),
                 local_ip: selected_interface_val,
                 local_port: port_num,
+                keepalive: keepalive.read().clone(),
+                proxy: proxy.read().clone(),
+                dial_plan: dial_plan.read().clone(),
+                dial_policy: dial_policy.read().clone(),
+                privacy: privacy.read().clone(),
+                extra_headers: extra_headers.read().clone(),
+                codecs: codecs.read().clone(),
+                srtp: *srtp.read(),
+                recording: recording.read().clone(),
+                music_on_hold: music_on_hold.read().clone(),
+                ringing: ringing.read().clone(),
             });
         }
     };
@@ -587,13 +1054,19 @@ pub fn App() -> Element {
     let on_make_call = {
         let sip_coroutine = sip_coroutine.clone();
         let call_target = call_target.clone();
+        let mut call_options = call_options.clone();
         
         move |_| {
             let target = call_target.read().clone();
             info!("Making call to: {}", target);
             
+            // The caller-ID choice applies to this call only; per-call
+            // headers stay in the options panel until edited.
+            let options = call_options.read().clone();
+            call_options.write().hide_caller_id = None;
+            
             // Send make call command to coroutine
-            sip_coroutine.send(SipCommand::MakeCall { target });
+            sip_coroutine.send(SipCommand::MakeCall { target, options, override_pin: None });
         }
     };
     
@@ -640,13 +1113,14 @@ pub fn App() -> Element {
             sip_coroutine.send(SipCommand::Hangup);
         }
     };
-    
+
     // Logout handler
     let on_logout = {
         let mut app_state = app_state.clone();
         let mut registration_state = registration_state.clone();
 
         move |_| {
+            logging::set_account(None);
             registration_state.set(CallState::Idle);
             app_state.set(AppState::Registration);
         }
@@ -681,9 +1155,27 @@ pub fn App() -> Element {
                             username: username.clone(),
                             password: password.clone(),
                             server_uri: server_uri.clone(),
+                            connection_mode: connection_mode.clone(),
+                            auth_username: auth_username.clone(),
+                            sip_domain: sip_domain.clone(),
+                            realm: realm.clone(),
+                            display_name: display_name.clone(),
                             selected_interface: selected_interface.clone(),
                             port: port.clone(),
+                            keepalive: keepalive.clone(),
+                            proxy: proxy.clone(),
+                            dial_plan: dial_plan.clone(),
+                            dial_policy: dial_policy.clone(),
+                            privacy: privacy.clone(),
+                            extra_headers: extra_headers.clone(),
+                            codecs: codecs.clone(),
+                            srtp: srtp.clone(),
+                            recording: recording.clone(),
+                            music_on_hold: music_on_hold.clone(),
+                            ringing: ringing.clone(),
                             registration_state: registration_state.clone(),
+                            sip_coroutine: sip_coroutine.clone(),
+                            diagnostics_notice: diagnostics_notice.clone(),
                             on_register: on_register,
                             on_skip: on_skip,
                         }
@@ -692,6 +1184,7 @@ pub fn App() -> Element {
                         CallInterfaceScreen {
                             username: username.read().clone(),
                             server_uri: server_uri.read().clone(),
+                            connection_mode: connection_mode.read().resolve(&server_uri.read()),
                             selected_interface: selected_interface.read().clone(),
                             port: port.read().clone(),
                             sip_coroutine: sip_coroutine.clone(),
@@ -700,14 +1193,27 @@ pub fn App() -> Element {
                             is_on_hook: is_on_hook.clone(),
                             audio_levels: audio_levels.clone(),
                             transfer_in_progress: transfer_in_progress.clone(),
+                            registrar_reachable: *registrar_reachable.read(),
+                            dial_plan: dial_plan.read().clone(),
+                            sip_domain: sip_domain.read().clone(),
+                            policy_refusal: policy_refusal.clone(),
+                            call_history: call_history.clone(),
+                            sip_trace: sip_trace.clone(),
+                            capture_options: capture_options.clone(),
+                            capture_notice: capture_notice.clone(),
+                            diagnostics_notice: diagnostics_notice.clone(),
+                            playback: playback.clone(),
+                            playback_notice: playback_notice.clone(),
+                            call_options: call_options.clone(),
+                            hide_caller_id_default: privacy.read().hide_caller_id,
                             on_make_call: on_make_call,
                             on_hangup_call: on_hangup,
                             on_logout: on_logout,
                         }
                     },
-                    AppState::IncomingCall { caller_id } => rsx! {
+                    AppState::IncomingCall { caller } => rsx! {
                         IncomingCallScreen {
-                            caller_id: caller_id,
+                            caller: caller,
                             on_answer: on_answer_call,
                             on_ignore: on_reject_call,
                         }
@@ -716,4 +1222,41 @@ pub fn App() -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Log the call's quality summary (worst interval included) once it ends,
+/// so support can match complaints to network events.
+fn log_quality_summary(monitors: &mut HashMap<String, QualityMonitor>, call_id: &str) {
+    if let Some(summary) = monitors.remove(call_id).and_then(|m| m.summary(call_id)) {
+        info!("{}", summary);
+    }
+}
+
+/// A call's SIP messages (from the trace buffer) and captured RTP, in
+/// time order.
+fn capture_packets(trace: &SipTrace, sip_client: &SipClientManager, call_id: &str) -> Vec<CapturedPacket> {
+    let mut packets: Vec<_> = trace
+        .messages(None)
+        .filter(|m| m.session_id.as_deref() == Some(call_id))
+        .filter_map(|m| m.to_packet())
+        .collect();
+    packets.extend(sip_client.rtp_packets(call_id));
+    packets.sort_by_key(|p| p.at);
+    packets
+}
+
+async fn write_capture(path: &Path, packets: &[CapturedPacket], call_id: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(path, pcap::pcapng(packets, &format!("Call {}", call_id))).await?;
+    Ok(())
+}
+
+/// Reason text when `e` is a call refused by the dialing policy.
+fn refusal_reason(e: &anyhow::Error) -> Option<String> {
+    match e.downcast_ref::<SipError>() {
+        Some(SipError::InvalidParameters(reason)) => Some(reason.clone()),
+        _ => None,
+    }
+}
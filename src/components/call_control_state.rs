@@ -49,7 +49,7 @@ impl CallControlState {
                 hook_style: ButtonStyle::Normal,
             },
             
-            Some(CallState::Calling) => Self {
+            Some(CallState::Calling) | Some(CallState::EarlyMedia) => Self {
                 make_call_enabled: false,
                 make_call_visible: false,
                 mute_enabled: false,
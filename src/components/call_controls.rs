@@ -1,19 +1,28 @@
 use dioxus::prelude::*;
 use lucide_dioxus::{Phone, PhoneOff, Mic, MicOff, Pause, Play, PhoneForwarded, PhoneIncoming};
-use crate::sip_client::CallState;
+use crate::sip_client::{CallOptions, CallState};
 use crate::components::call_control_state::{CallControlState, ButtonStyle};
+use crate::components::CallOptionsPanel;
 
 #[component]
 pub fn CallControls(
     call_state: Option<CallState>,
     is_muted: bool,
+    is_recording: bool,
     is_on_hook: bool,
     call_target: Signal<String>,
+    /// Normalized request URI for `call_target`, or why it cannot be dialed
+    dial_preview: Option<Result<String, String>>,
+    /// Options for the next call (reset once it is placed)
+    mut call_options: Signal<CallOptions>,
+    /// Profile default for hiding caller ID
+    hide_caller_id_default: bool,
     is_p2p_mode: bool,
     is_receiver_mode: bool,
     on_make_call: EventHandler<()>,
     on_mute_toggle: EventHandler<()>,
     on_hold_toggle: EventHandler<()>,
+    on_record_toggle: EventHandler<()>,
     on_transfer: EventHandler<()>,
     on_hook_toggle: EventHandler<()>,
     on_end_call: EventHandler<()>
@@ -26,6 +35,11 @@ pub fn CallControls(
     log::info!("CallControls: control_state.mute_enabled = {}, hold_enabled = {}, end_call_visible = {}", 
         control_state.mute_enabled, control_state.hold_enabled, control_state.end_call_visible);
     
+    // Recording needs media, so only once the call is up
+    let record_enabled = matches!(call_state, Some(CallState::Connected | CallState::OnHold));
+
+    let hide_caller_id = call_options.read().hide_caller_id.unwrap_or(hide_caller_id_default);
+    
     // Determine placeholder text
     let placeholder = if is_receiver_mode {
         "Enter SIP URI (e.g., alice@192.168.1.100)"
@@ -78,6 +92,36 @@ pub fn CallControls(
                             span { "Call" }
                         }
                     }
+                    match &dial_preview {
+                        Some(Ok(uri)) => rsx! {
+                            p {
+                                class: "mt-2 text-xs text-gray-500 font-mono truncate",
+                                title: "{uri}",
+                                "Dials {uri}"
+                            }
+                        },
+                        Some(Err(reason)) => rsx! {
+                            p {
+                                class: "mt-2 text-xs text-red-600",
+                                "Invalid address: {reason}"
+                            }
+                        },
+                        None => rsx! {},
+                    }
+                    label {
+                        class: "mt-2 inline-flex items-center gap-2 text-sm text-gray-600",
+                        input {
+                            r#type: "checkbox",
+                            checked: hide_caller_id,
+                            disabled: !control_state.make_call_enabled,
+                            oninput: move |evt| call_options.write().hide_caller_id = Some(evt.checked()),
+                        }
+                        "Hide caller ID"
+                    }
+                    CallOptionsPanel {
+                        call_options: call_options,
+                        disabled: !control_state.make_call_enabled,
+                    }
                 }
             }
             
@@ -143,6 +187,26 @@ pub fn CallControls(
                     }
                 }
                 
+                // Record button
+                button {
+                    class: if record_enabled {
+                        if is_recording {
+                            "w-16 h-16 bg-red-600 hover:bg-red-700 text-white rounded-lg transition-all duration-200 shadow-sm hover:shadow-md flex items-center justify-center"
+                        } else {
+                            "w-16 h-16 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-lg transition-all duration-200 shadow-sm hover:shadow-md flex items-center justify-center"
+                        }
+                    } else {
+                        "w-16 h-16 bg-gray-100 text-gray-400 rounded-lg cursor-not-allowed opacity-50 flex items-center justify-center"
+                    },
+                    disabled: !record_enabled,
+                    onclick: move |_| if record_enabled { on_record_toggle.call(()) },
+                    title: if is_recording { "Stop recording" } else { "Record call" },
+                    span {
+                        class: "text-xl leading-none",
+                        if is_recording { "■" } else { "●" }
+                    }
+                }
+                
                 // Transfer button
                 button {
                     class: if control_state.transfer_enabled {
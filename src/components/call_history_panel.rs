@@ -0,0 +1,110 @@
+use dioxus::prelude::*;
+use lucide_dioxus::{PhoneIncoming, PhoneOutgoing};
+use crate::call_history::{CallHistory, CallOutcome};
+
+/// Recent calls, newest first. Clicking an entry puts its address in the
+/// dial box (unless the caller withheld it).
+#[component]
+pub fn CallHistoryPanel(
+    mut history: Signal<CallHistory>,
+    mut call_target: Signal<String>,
+) -> Element {
+    if history.read().is_empty() {
+        return rsx! {};
+    }
+    let records: Vec<_> = history.read().iter().cloned().collect();
+
+    rsx! {
+        div {
+            class: "bg-white rounded-xl p-6 shadow-sm border border-gray-200",
+            div {
+                class: "flex justify-between items-center mb-3",
+                h3 { class: "text-sm font-semibold text-gray-700", "Recent Calls" }
+                button {
+                    class: "text-xs text-gray-500 hover:text-gray-700",
+                    onclick: move |_| history.write().clear(),
+                    "Clear"
+                }
+            }
+            ul {
+                class: "flex flex-col divide-y divide-gray-100 max-h-64 overflow-y-auto",
+                for (index, record) in records.into_iter().enumerate() {
+                    {
+                        let callback = record.identity.callback();
+                        let when = record.at.with_timezone(&chrono::Local).format("%H:%M").to_string();
+                        let duration = record
+                            .duration
+                            .map(|d| format!(" • {:02}:{:02}", d.as_secs() / 60, d.as_secs() % 60))
+                            .unwrap_or_default();
+                        let quality = record
+                            .stats
+                            .as_ref()
+                            .map(|s| format!("MOS {:.1}, {:.1}% loss", s.mos(), s.loss_percent()));
+                        let outcome_class = match record.outcome {
+                            CallOutcome::Missed | CallOutcome::Failed(_) | CallOutcome::Unencrypted => "text-xs text-red-600",
+                            _ => "text-xs text-gray-500",
+                        };
+                        rsx! {
+                            li {
+                                key: "{index}",
+                                class: if callback.is_some() {
+                                    "flex items-center gap-3 py-2 cursor-pointer hover:bg-gray-50"
+                                } else {
+                                    "flex items-center gap-3 py-2"
+                                },
+                                title: callback.clone().unwrap_or_default(),
+                                onclick: move |_| {
+                                    if let Some(target) = &callback {
+                                        call_target.set(target.clone());
+                                    }
+                                },
+                                if record.is_incoming {
+                                    PhoneIncoming { size: 16, color: "currentColor", stroke_width: 2 }
+                                } else {
+                                    PhoneOutgoing { size: 16, color: "currentColor", stroke_width: 2 }
+                                }
+                                div {
+                                    class: "flex-1 min-w-0",
+                                    p { class: "text-sm text-gray-800 truncate", "{record.identity.label()}" }
+                                    if let Some(detail) = record.identity.detail() {
+                                        p { class: "text-xs text-gray-500 truncate", "{detail}" }
+                                    }
+                                }
+                                div {
+                                    class: "text-right",
+                                    p { class: "text-xs text-gray-500", "{when}{duration}" }
+                                    p { class: outcome_class, "{record.outcome.label()}" }
+                                    if let Some(quality) = quality {
+                                        p { class: "text-xs text-gray-400", "{quality}" }
+                                    }
+                                    if let Some(path) = &record.capture {
+                                        p {
+                                            class: "text-xs text-gray-400",
+                                            title: "{path.display()}",
+                                            "pcap saved"
+                                        }
+                                    }
+                                    for path in record.recordings.iter().cloned() {
+                                        button {
+                                            class: "block ml-auto text-xs text-blue-600 hover:underline",
+                                            r#type: "button",
+                                            title: "{path.display()}",
+                                            onclick: move |evt| {
+                                                // Don't also put the number in the dial box
+                                                evt.stop_propagation();
+                                                if let Err(e) = crate::recording::open(&path) {
+                                                    log::error!("Failed to open recording {}: {}", path.display(), e);
+                                                }
+                                            },
+                                            "▶ Recording"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
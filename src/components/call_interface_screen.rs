@@ -1,13 +1,20 @@
 use dioxus::prelude::*;
-use crate::sip_client::{CallInfo, CallState};
+use crate::dial_plan::DialPlan;
+use crate::sip_client::{CallInfo, CallOptions, CallState, CaptureOptions, ModeSelection};
+use crate::sip_uri;
 use crate::commands::SipCommand;
-use crate::components::{UserInfoBar, CallStatus, CallControls, HookStatus, TransferDialog, DtmfKeypad, AudioPanel};
+use crate::components::{UserInfoBar, CallStatus, CallControls, HookStatus, TransferDialog, DtmfKeypad, AudioPanel, DialPolicyNotice, CallHistoryPanel, CallStatsPanel, SipTracePanel, SaveCaptureButton, DiagnosticsButton, LogViewerPanel, PlaybackPanel};
+use crate::sip_trace::SipTrace;
+use crate::call_history::CallHistory;
+use crate::components::dial_policy_notice::PolicyRefusal;
 use crate::components::call_control_state::CallControlState;
+use crate::playback::PlaybackProgress;
 
 #[component]
 pub fn CallInterfaceScreen(
     username: String,
     server_uri: String,
+    connection_mode: ModeSelection,
     selected_interface: Option<String>,
     port: String,
     sip_coroutine: Coroutine<SipCommand>,
@@ -16,12 +23,25 @@ pub fn CallInterfaceScreen(
     is_on_hook: Signal<bool>,
     audio_levels: Signal<(f32, f32)>,
     transfer_in_progress: Signal<bool>,
+    registrar_reachable: bool,
+    dial_plan: DialPlan,
+    sip_domain: String,
+    policy_refusal: Signal<Option<PolicyRefusal>>,
+    call_history: Signal<CallHistory>,
+    sip_trace: Signal<SipTrace>,
+    capture_options: Signal<CaptureOptions>,
+    capture_notice: Signal<Option<String>>,
+    diagnostics_notice: Signal<Option<String>>,
+    playback: Signal<Option<(String, PlaybackProgress)>>,
+    playback_notice: Signal<Option<String>>,
+    call_options: Signal<CallOptions>,
+    hide_caller_id_default: bool,
     on_make_call: EventHandler<()>,
     on_hangup_call: EventHandler<()>,
     on_logout: EventHandler<()>
 ) -> Element {
-    // Determine connection mode from the actual SipClientManager config
-    let is_p2p_mode = server_uri.contains('@');
+    // Connection mode as resolved when the client was initialized
+    let is_p2p_mode = connection_mode == ModeSelection::PeerToPeer;
     let mut is_receiver_mode = use_signal(|| false);
     
     // Get listening address for receiver mode
@@ -30,8 +50,7 @@ pub fn CallInterfaceScreen(
     // Transfer dialog state
     let mut show_transfer_dialog = use_signal(|| false);
     
-    // Check if in receiver mode based on server_uri
-    is_receiver_mode.set(server_uri.is_empty());
+    is_receiver_mode.set(connection_mode == ModeSelection::Receiver);
     
     // Set listening address for receiver mode
     if *is_receiver_mode.read() {
@@ -83,6 +102,7 @@ pub fn CallInterfaceScreen(
     let call_info = current_call.read().clone();
     let call_state = call_info.as_ref().map(|c| c.state.clone());
     let is_muted = call_info.as_ref().and_then(|c| c.is_muted).unwrap_or(false);
+    let is_recording = call_info.as_ref().is_some_and(|c| c.recording.is_some());
     
     // Debug logging
     log::info!("CallInterfaceScreen: call_info = {:?}", call_info);
@@ -103,6 +123,7 @@ pub fn CallInterfaceScreen(
             // BUT: Don't do this for incoming ringing calls as they're being answered
             match call_state {
                 Some(CallState::Calling) |  // Outgoing calls
+                Some(CallState::EarlyMedia) |
                 Some(CallState::Connected) | // Active calls
                 Some(CallState::OnHold) | 
                 Some(CallState::Transferring) => {
@@ -126,13 +147,34 @@ pub fn CallInterfaceScreen(
         }
     });
     
+    // Normalized dial target, previewed under the dial box before calling.
+    // Mirrors SipClientManager::format_target_uri: bare numbers go to the SIP
+    // domain (or server / peer host).
+    let dial_host = match connection_mode {
+        ModeSelection::Receiver => None,
+        _ => {
+            let host = if sip_domain.trim().is_empty() { &server_uri } else { &sip_domain };
+            sip_uri::dial_target(host, None).ok().map(|uri| uri.host_port())
+        }
+    };
+    let dial_preview = {
+        let target = call_target.read();
+        let target = target.trim();
+        if target.is_empty() {
+            None
+        } else {
+            match dial_plan.target(target, dial_host.as_deref()) {
+                Ok(uri) => Some(Ok(uri.to_string())),
+                Err(e) => Some(Err(e.to_string())),
+            }
+        }
+    };
+    
     // Compute status text
-    let status_text = if is_p2p_mode {
-        format!("P2P: {}", server_uri)
-    } else if !server_uri.is_empty() {
-        format!("Server: {}", server_uri)
-    } else {
-        "No server configured".to_string()
+    let status_text = match connection_mode {
+        ModeSelection::PeerToPeer => format!("P2P: {}", server_uri),
+        ModeSelection::Server | ModeSelection::Auto => format!("Server: {}", server_uri),
+        ModeSelection::Receiver => "No server configured".to_string(),
     };
     
     rsx! {
@@ -147,6 +189,7 @@ pub fn CallInterfaceScreen(
                 UserInfoBar {
                     username: username.clone(),
                     status_text: status_text,
+                    registrar_reachable: registrar_reachable,
                     on_logout: move |_| on_logout.call(())
                 }
                 
@@ -155,6 +198,16 @@ pub fn CallInterfaceScreen(
                     CallStatus {
                         call: current_call.clone()
                     }
+                    CallStatsPanel {
+                        call: current_call.clone()
+                    }
+                }
+                if let Some(call_id) = current_call.read().as_ref().map(|c| c.id.clone()) {
+                    SaveCaptureButton {
+                        call_id: call_id,
+                        sip_coroutine: sip_coroutine.clone(),
+                        notice: capture_notice,
+                    }
                 }
                 
                 // Call controls - always visible
@@ -163,8 +216,12 @@ pub fn CallInterfaceScreen(
                     CallControls {
                     call_state: call_state,
                     is_muted: is_muted,
+                    is_recording: is_recording,
                     is_on_hook: *is_on_hook.read(),
                     call_target: call_target.clone(),
+                    dial_preview: dial_preview,
+                    call_options: call_options,
+                    hide_caller_id_default: hide_caller_id_default,
                     is_p2p_mode: is_p2p_mode,
                     is_receiver_mode: *is_receiver_mode.read(),
                     on_make_call: move |_| on_make_call.call(()),
@@ -185,6 +242,10 @@ pub fn CallInterfaceScreen(
                             }
                         }
                     },
+                    on_record_toggle: move |_| {
+                        log::info!("Record button clicked");
+                        sip_coroutine.send(SipCommand::ToggleRecording);
+                    },
                     on_transfer: move |_| {
                         log::info!("Transfer button clicked");
                         show_transfer_dialog.set(true);
@@ -197,6 +258,12 @@ pub fn CallInterfaceScreen(
                     }
                 }
 
+                // Dialing policy refusal (with PIN override when configured)
+                DialPolicyNotice {
+                    refusal: policy_refusal,
+                    sip_coroutine: sip_coroutine.clone(),
+                }
+
                 // Attended transfer controls (shown during a consultation)
                 if *transfer_in_progress.read() {
                     div {
@@ -217,10 +284,34 @@ pub fn CallInterfaceScreen(
                 // DTMF keypad during an active call
                 if is_connected {
                     DtmfKeypad { sip_coroutine }
+                    PlaybackPanel { sip_coroutine, playback, notice: playback_notice }
                 }
 
                 // Audio device selection + level meters
                 AudioPanel { sip_coroutine, audio_levels }
+
+                // Recent calls while idle
+                if current_call.read().is_none() {
+                    CallHistoryPanel {
+                        history: call_history,
+                        call_target: call_target.clone(),
+                    }
+                }
+
+                // Raw SIP for troubleshooting failed calls
+                SipTracePanel {
+                    trace: sip_trace,
+                    capture_options: capture_options,
+                    sip_coroutine: sip_coroutine.clone(),
+                }
+
+                // Structured log of this session, filterable by call
+                LogViewerPanel {}
+
+                DiagnosticsButton {
+                    sip_coroutine: sip_coroutine.clone(),
+                    notice: diagnostics_notice,
+                }
             }
 
             // Transfer dialog
@@ -228,12 +319,12 @@ pub fn CallInterfaceScreen(
                 is_open: *show_transfer_dialog.read(),
                 on_transfer: move |target| {
                     log::info!("Blind transfer to: {}", target);
-                    sip_coroutine.send(SipCommand::Transfer { target });
+                    sip_coroutine.send(SipCommand::Transfer { target, override_pin: None });
                     show_transfer_dialog.set(false);
                 },
                 on_attended: move |target| {
                     log::info!("Attended transfer to: {}", target);
-                    sip_coroutine.send(SipCommand::StartAttendedTransfer { target });
+                    sip_coroutine.send(SipCommand::StartAttendedTransfer { target, override_pin: None });
                     show_transfer_dialog.set(false);
                 },
                 on_close: move |_| {
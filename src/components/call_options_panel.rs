@@ -0,0 +1,58 @@
+use dioxus::prelude::*;
+use crate::sip_client::CallOptions;
+use crate::sip_headers;
+
+/// Collapsible "Advanced call options" under the dial box: extra INVITE
+/// headers for the next calls (e.g. `Alert-Info`, `X-Account-Code`).
+#[component]
+pub fn CallOptionsPanel(mut call_options: Signal<CallOptions>, disabled: bool) -> Element {
+    let mut expanded = use_signal(|| false);
+    let mut headers_text = use_signal(|| sip_headers::format_lines(&call_options.read().headers));
+
+    let header_count = call_options.read().headers.len();
+    let error = sip_headers::parse_lines(&headers_text.read()).err();
+    let toggle_label = match (*expanded.read(), header_count) {
+        (true, _) => "▾ Advanced call options".to_string(),
+        (false, 0) => "▸ Advanced call options".to_string(),
+        (false, n) => format!("▸ Advanced call options ({} header{})", n, if n == 1 { "" } else { "s" }),
+    };
+
+    rsx! {
+        div {
+            class: "mt-2",
+            button {
+                class: "text-sm text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                "{toggle_label}"
+            }
+            if *expanded.read() {
+                div {
+                    class: "mt-2",
+                    textarea {
+                        class: "w-full px-4 py-3 border border-gray-300 rounded-lg text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                        rows: "3",
+                        placeholder: "Extra INVITE headers, one per line\nAlert-Info: <http://example.com/ring>;info=alert-internal\nX-Account-Code: 4711",
+                        value: "{headers_text}",
+                        disabled: disabled,
+                        oninput: move |evt| {
+                            let text = evt.value();
+                            if let Ok(headers) = sip_headers::parse_lines(&text) {
+                                call_options.write().headers = headers;
+                            }
+                            headers_text.set(text);
+                        }
+                    }
+                    if let Some(error) = error {
+                        p { class: "mt-1 text-xs text-red-600", "{error}" }
+                    } else {
+                        p { class: "mt-1 text-xs text-gray-500", "Sent on every call until cleared; they replace profile headers of the same name." }
+                    }
+                }
+            }
+        }
+    }
+}
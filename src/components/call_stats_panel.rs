@@ -0,0 +1,57 @@
+use dioxus::prelude::*;
+use crate::sip_client::CallInfo;
+
+/// Collapsible "Call quality" panel under the call status: RTCP packet
+/// counts, loss, jitter, RTT, jitter-buffer depth and estimated MOS.
+#[component]
+pub fn CallStatsPanel(call: Signal<Option<CallInfo>>) -> Element {
+    let mut expanded = use_signal(|| false);
+
+    let Some(stats) = call.read().as_ref().and_then(|c| c.stats.clone()) else {
+        return rsx! {};
+    };
+    let mos = stats.mos();
+    let rating = stats.rating();
+    let rating_class = match rating {
+        "Excellent" | "Good" => "text-green-700",
+        "Fair" => "text-yellow-700",
+        _ => "text-red-700",
+    };
+    let rows = [
+        ("Codec", stats.codec.clone().unwrap_or_else(|| "—".to_string())),
+        ("Packets sent", stats.packets_sent.to_string()),
+        ("Packets received", stats.packets_received.to_string()),
+        ("Packet loss", format!("{:.1}% ({} lost)", stats.loss_percent(), stats.packets_lost)),
+        ("Jitter", format!("{:.1} ms", stats.jitter_ms)),
+        ("Round trip", stats.rtt_ms.map(|ms| format!("{:.0} ms", ms)).unwrap_or_else(|| "—".to_string())),
+        ("Jitter buffer", stats.jitter_buffer_ms.map(|ms| format!("{} ms", ms)).unwrap_or_else(|| "—".to_string())),
+    ];
+
+    rsx! {
+        div {
+            class: "bg-white rounded-xl px-6 py-3 shadow-sm border border-gray-200",
+            button {
+                class: "w-full flex justify-between items-center text-sm text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                span { if *expanded.read() { "▾ Call quality" } else { "▸ Call quality" } }
+                span {
+                    class: "font-medium {rating_class}",
+                    "{rating} • MOS {mos:.1}"
+                }
+            }
+            if *expanded.read() {
+                dl {
+                    class: "mt-3 grid grid-cols-2 gap-x-4 gap-y-1 text-sm",
+                    for (label, value) in rows {
+                        dt { class: "text-gray-500", "{label}" }
+                        dd { class: "text-gray-800 font-mono text-right", "{value}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -13,6 +13,7 @@ pub fn CallStatus(
     let status_text = match call_info.state {
         CallState::Calling => "Calling...".to_string(),
         CallState::Ringing => "Ringing...".to_string(),
+        CallState::EarlyMedia => "Call in progress...".to_string(),
         CallState::Connected => {
             if let Some(duration) = &call_info.duration {
                 format!("Connected • {:02}:{:02}", duration.as_secs() / 60, duration.as_secs() % 60)
@@ -36,6 +37,7 @@ pub fn CallStatus(
     let status_icon = match call_info.state {
         CallState::Calling => "🔄",
         CallState::Ringing => "🔔",
+        CallState::EarlyMedia => "🔈",
         CallState::Connected => "",
         CallState::OnHold => "⏸️",
         CallState::Transferring => "⏳",
@@ -51,7 +53,13 @@ pub fn CallStatus(
                 class: "mb-2",
                 h2 {
                     class: "text-2xl font-semibold text-gray-900",
-                    "{call_info.remote_uri}"
+                    "{call_info.identity.label()}"
+                }
+                if let Some(detail) = call_info.identity.detail() {
+                    p {
+                        class: "text-sm text-gray-500",
+                        "{detail}"
+                    }
                 }
             }
             
@@ -60,7 +68,7 @@ pub fn CallStatus(
                 class: "flex items-center justify-center gap-2",
                 if !status_icon.is_empty() {
                     span {
-                        class: if matches!(call_info.state, CallState::Calling | CallState::Ringing) {
+                        class: if matches!(call_info.state, CallState::Calling | CallState::Ringing | CallState::EarlyMedia) {
                             "animate-pulse"
                         } else {
                             ""
@@ -72,8 +80,60 @@ pub fn CallStatus(
                     class: "text-lg text-gray-600",
                     "{status_text}"
                 }
+                if let Some(suite) = call_info.media.as_ref().and_then(|m| m.srtp_suite.as_ref()) {
+                    span {
+                        class: "text-lg",
+                        title: "Media encrypted (SRTP {suite})",
+                        "🔒"
+                    }
+                }
             }
             
+            // Negotiated codec, once SDP offer/answer completes
+            if let Some(media) = call_info.media.as_ref().filter(|_| {
+                matches!(call_info.state, CallState::Connected | CallState::OnHold)
+            }) {
+                p {
+                    class: "mt-1 text-xs text-gray-500 font-mono",
+                    title: "RTP payload type {media.payload_type}",
+                    "{media}"
+                }
+            }
+
+            // Sustained loss/jitter (see QualityMonitor)
+            if let Some(warning) = call_info.quality_warning.as_ref().filter(|_| {
+                matches!(call_info.state, CallState::Connected | CallState::OnHold)
+            }) {
+                div {
+                    class: "mt-2",
+                    span {
+                        class: "inline-flex items-center px-3 py-1 rounded-full text-sm font-medium bg-yellow-100 text-yellow-800",
+                        "⚠️ {warning}"
+                    }
+                }
+            }
+
+            // Shown for as long as the call is being recorded
+            if call_info.recording.is_some() {
+                div {
+                    class: "mt-2",
+                    span {
+                        class: "inline-flex items-center gap-1 px-3 py-1 rounded-full text-sm font-semibold bg-red-600 text-white",
+                        title: "This call is being recorded",
+                        span { class: "animate-pulse", "●" }
+                        "REC"
+                    }
+                }
+            } else if let Some(reason) = call_info.recording_refused.as_ref() {
+                div {
+                    class: "mt-2",
+                    span {
+                        class: "inline-flex items-center px-3 py-1 rounded-full text-sm font-medium bg-yellow-100 text-yellow-800",
+                        "⚠️ Not recording: {reason}"
+                    }
+                }
+            }
+
             // Additional status info for special states
             if call_info.is_muted.unwrap_or(false) {
                 div {
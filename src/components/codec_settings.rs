@@ -0,0 +1,190 @@
+use dioxus::prelude::*;
+
+use crate::codecs::{Codec, CodecPreferences};
+use crate::srtp::SrtpPolicy;
+
+/// Collapsible "Codecs" section of the registration form: media encryption,
+/// offer order, enabled codecs, Opus bitrate/FEC/DTX and packetization time.
+/// Shown in every mode, since P2P and Receiver calls negotiate media too.
+#[component]
+pub fn CodecSettings(
+    mut codecs: Signal<CodecPreferences>,
+    mut srtp: Signal<SrtpPolicy>,
+    disabled: bool,
+) -> Element {
+    let mut expanded = use_signal(|| false);
+
+    let prefs = codecs.read().clone();
+    let summary = prefs
+        .enabled()
+        .iter()
+        .map(|c| c.sdp_name())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let error = prefs.validate().err();
+    let opus_enabled = prefs.enabled().contains(&Codec::Opus);
+    let last = prefs.order.len().saturating_sub(1);
+    let policy = *srtp.read();
+    let summary = match policy {
+        SrtpPolicy::Off => summary,
+        _ => format!("{}, SRTP {}", summary, policy.id()),
+    };
+
+    rsx! {
+        div {
+            class: "border-t border-gray-200 pt-4",
+            button {
+                class: "text-sm font-medium text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                if *expanded.read() { "▾ Codecs" } else { "▸ Codecs ({summary})" }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "flex flex-col gap-4 mt-4",
+
+                    // SDES-SRTP policy
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Media Encryption"
+                        }
+                        select {
+                            class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            disabled: disabled,
+                            oninput: move |evt| srtp.set(SrtpPolicy::from_id(&evt.value())),
+                            for option_policy in SrtpPolicy::all() {
+                                option {
+                                    value: option_policy.id(),
+                                    selected: policy == option_policy,
+                                    "{option_policy.label()}"
+                                }
+                            }
+                        }
+                        if policy == SrtpPolicy::Mandatory {
+                            p { class: "mt-1 text-xs text-gray-500", "Calls to or from parties without SRTP are refused. Keys are exchanged in SDP, so use a sips: or transport=tls server to keep them private." }
+                        }
+                    }
+
+                    // Offer order
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Preference Order"
+                        }
+                        div {
+                            class: "flex flex-col border border-gray-300 rounded-md divide-y divide-gray-200 bg-white",
+                            for (index, (codec, enabled)) in prefs.order.iter().copied().enumerate() {
+                                div {
+                                    key: "{codec.sdp_name()}",
+                                    class: "flex items-center gap-3 px-4 py-2",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: enabled,
+                                        disabled: disabled,
+                                        oninput: move |evt| codecs.write().order[index].1 = evt.checked(),
+                                    }
+                                    span {
+                                        class: if enabled { "flex-1 text-sm text-gray-700" } else { "flex-1 text-sm text-gray-400" },
+                                        "{codec.label()}"
+                                    }
+                                    button {
+                                        class: "px-2 text-sm text-gray-500 hover:text-gray-800 disabled:text-gray-300 disabled:cursor-not-allowed",
+                                        r#type: "button",
+                                        disabled: disabled || index == 0,
+                                        onclick: move |_| codecs.write().move_codec(index, true),
+                                        "↑"
+                                    }
+                                    button {
+                                        class: "px-2 text-sm text-gray-500 hover:text-gray-800 disabled:text-gray-300 disabled:cursor-not-allowed",
+                                        r#type: "button",
+                                        disabled: disabled || index == last,
+                                        onclick: move |_| codecs.write().move_codec(index, false),
+                                        "↓"
+                                    }
+                                }
+                            }
+                        }
+                        if let Some(error) = error {
+                            p { class: "mt-1 text-xs text-red-600", "{error}" }
+                        } else {
+                            p { class: "mt-1 text-xs text-gray-500", "Offered in this order; the remote picks the first one it supports." }
+                        }
+                    }
+
+                    // Opus options and ptime
+                    div {
+                        class: "flex gap-3",
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Opus Bitrate (kbit/s)"
+                            }
+                            input {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "number",
+                                value: "{prefs.opus.max_bitrate_kbps}",
+                                min: "6",
+                                max: "510",
+                                disabled: disabled || !opus_enabled,
+                                oninput: move |evt| {
+                                    // Clamped to 6–510 when the fmtp is built
+                                    if let Ok(kbps) = evt.value().parse::<u32>() {
+                                        codecs.write().opus.max_bitrate_kbps = kbps;
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Packet Time"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    if let Ok(ms) = evt.value().parse::<u32>() {
+                                        codecs.write().ptime_ms = ms;
+                                    }
+                                },
+                                for ms in [10u32, 20, 30, 40, 60] {
+                                    option { value: "{ms}", selected: prefs.ptime_ms == ms, "{ms} ms" }
+                                }
+                            }
+                        }
+                    }
+                    div {
+                        class: "flex gap-6",
+                        label {
+                            class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: prefs.opus.fec,
+                                disabled: disabled || !opus_enabled,
+                                oninput: move |evt| codecs.write().opus.fec = evt.checked(),
+                            }
+                            "Opus in-band FEC"
+                        }
+                        label {
+                            class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: prefs.opus.dtx,
+                                disabled: disabled || !opus_enabled,
+                                oninput: move |evt| codecs.write().opus.dtx = evt.checked(),
+                            }
+                            "Opus DTX"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
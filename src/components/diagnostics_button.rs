@@ -0,0 +1,42 @@
+use dioxus::prelude::*;
+use crate::commands::SipCommand;
+
+/// "Export diagnostics…" link: asks where to save the bundle, then has the
+/// coroutine collect and write it.
+#[component]
+pub fn DiagnosticsButton(
+    sip_coroutine: Coroutine<SipCommand>,
+    mut notice: Signal<Option<String>>,
+) -> Element {
+    rsx! {
+        div {
+            class: "flex justify-end items-center gap-3 text-xs",
+            if let Some(message) = notice.read().as_ref() {
+                span { class: "text-gray-500 truncate", title: "{message}", "{message}" }
+            }
+            button {
+                class: "text-gray-500 hover:text-gray-700 shrink-0",
+                r#type: "button",
+                title: "Config, logs, SIP trace, recent calls and devices for a support ticket; passwords are removed",
+                onclick: move |_| {
+                    spawn(async move {
+                        let file_name = format!("sip-client-diagnostics-{}.tar", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                        let Some(file) = rfd::AsyncFileDialog::new()
+                            .set_file_name(&file_name)
+                            .add_filter("tar", &["tar"])
+                            .save_file()
+                            .await
+                        else {
+                            return;
+                        };
+                        notice.set(None);
+                        sip_coroutine.send(SipCommand::ExportDiagnostics {
+                            path: file.path().to_path_buf(),
+                        });
+                    });
+                },
+                "Export diagnostics…"
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use dioxus::prelude::*;
+use crate::commands::SipCommand;
+use crate::sip_client::CallOptions;
+
+/// Which request the dialing policy refused, so an override can retry it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefusedAction {
+    Call,
+    Transfer,
+    /// Consultation leg of an attended transfer
+    Consultation,
+}
+
+/// A call or transfer refused by the dialing policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyRefusal {
+    pub target: String,
+    pub reason: String,
+    pub action: RefusedAction,
+    /// Options the refused call was placed with
+    pub options: CallOptions,
+    /// Whether a PIN override is configured and applicable
+    pub can_override: bool,
+}
+
+/// Red notice shown when the dialing policy refuses a call, with an optional
+/// PIN prompt to place it anyway.
+#[component]
+pub fn DialPolicyNotice(
+    mut refusal: Signal<Option<PolicyRefusal>>,
+    sip_coroutine: Coroutine<SipCommand>,
+) -> Element {
+    let mut pin = use_signal(|| "".to_string());
+
+    let Some(current) = refusal.read().clone() else {
+        return rsx! {};
+    };
+    let target = current.target.clone();
+    let options = current.options.clone();
+    let action = current.action;
+
+    rsx! {
+        div {
+            class: "bg-red-50 border border-red-200 rounded-xl p-4 flex flex-col gap-3",
+            div {
+                class: "flex justify-between items-start gap-3",
+                div {
+                    p { class: "text-sm font-semibold text-red-800", "Call not allowed" }
+                    p { class: "text-sm text-red-700 mt-1", "{current.reason}" }
+                }
+                button {
+                    class: "text-sm text-red-700 hover:text-red-900",
+                    onclick: move |_| {
+                        pin.set(String::new());
+                        refusal.set(None);
+                    },
+                    "Dismiss"
+                }
+            }
+            if current.can_override {
+                div {
+                    class: "flex gap-3",
+                    input {
+                        class: "flex-1 px-4 py-2 border border-red-300 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-red-400 focus:border-transparent",
+                        r#type: "password",
+                        placeholder: "Override PIN",
+                        value: "{pin}",
+                        oninput: move |evt| pin.set(evt.value()),
+                    }
+                    button {
+                        class: "px-4 py-2 bg-red-600 hover:bg-red-700 text-white text-sm rounded-lg disabled:bg-gray-100 disabled:text-gray-400 disabled:cursor-not-allowed",
+                        disabled: pin.read().is_empty(),
+                        onclick: move |_| {
+                            let override_pin = Some(pin.read().clone());
+                            let target = target.clone();
+                            let options = options.clone();
+                            pin.set(String::new());
+                            refusal.set(None);
+                            sip_coroutine.send(match action {
+                                RefusedAction::Call => SipCommand::MakeCall { target, options, override_pin },
+                                RefusedAction::Transfer => SipCommand::Transfer { target, override_pin },
+                                RefusedAction::Consultation => SipCommand::StartAttendedTransfer { target, override_pin },
+                            });
+                        },
+                        "Override"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+/// Collapsible "Hold" section of the registration form: the music-on-hold
+/// file looped to the other party while we hold a call.
+#[component]
+pub fn HoldSettings(mut music_on_hold: Signal<Option<PathBuf>>, disabled: bool) -> Element {
+    let mut expanded = use_signal(|| false);
+
+    let file = music_on_hold.read().clone();
+    let summary = file
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "silence".to_string());
+
+    rsx! {
+        div {
+            class: "border-t border-gray-200 pt-4",
+            button {
+                class: "text-sm font-medium text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                if *expanded.read() { "▾ Hold" } else { "▸ Hold ({summary})" }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "mt-4",
+                    label {
+                        class: "block text-sm font-medium text-gray-700 mb-2",
+                        "Music on hold"
+                    }
+                    div {
+                        class: "flex gap-3",
+                        input {
+                            class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: "None (the other party hears silence)",
+                            value: file.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                            disabled: disabled,
+                            oninput: move |evt| {
+                                let value = evt.value();
+                                music_on_hold.set((!value.trim().is_empty()).then(|| PathBuf::from(value.trim())));
+                            },
+                        }
+                        button {
+                            class: "px-4 py-3 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-md text-sm disabled:opacity-50",
+                            r#type: "button",
+                            disabled: disabled,
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(file) = rfd::AsyncFileDialog::new()
+                                        .add_filter("Audio", &["wav", "opus", "ogg"])
+                                        .pick_file()
+                                        .await
+                                    {
+                                        music_on_hold.set(Some(file.path().to_path_buf()));
+                                    }
+                                });
+                            },
+                            "Choose…"
+                        }
+                    }
+                    p { class: "mt-1 text-xs text-gray-500", "WAV or Ogg Opus, played in a loop while you hold a call. Leave empty if your PBX provides hold music." }
+                }
+            }
+        }
+    }
+}
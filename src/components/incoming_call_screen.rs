@@ -1,8 +1,9 @@
 use dioxus::prelude::*;
+use crate::caller_id::CallerIdentity;
 
 #[component]
 pub fn IncomingCallScreen(
-    caller_id: String,
+    caller: CallerIdentity,
     on_answer: EventHandler<()>,
     on_ignore: EventHandler<()>
 ) -> Element {
@@ -20,7 +21,19 @@ pub fn IncomingCallScreen(
                 
                 p {
                     class: "text-lg text-green-600 font-medium",
-                    "{caller_id}"
+                    "{caller.label()}"
+                }
+                if let Some(detail) = caller.detail() {
+                    p {
+                        class: "text-sm text-gray-500 mt-1",
+                        "{detail}"
+                    }
+                }
+                if caller.is_network_asserted() && !caller.anonymous {
+                    p {
+                        class: "text-xs text-gray-400 mt-1",
+                        "Verified by network"
+                    }
                 }
             }
             
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use log::Level;
+
+use crate::diagnostics::redact;
+use crate::logging::{self, LogEntry};
+
+/// Rows rendered at once; older matches are skipped (the bundle has them all).
+const MAX_ROWS: usize = 500;
+
+/// Collapsible "Log" pane: this session's structured log, filterable by
+/// minimum level and by call, with copy as JSON lines.
+#[component]
+pub fn LogViewerPanel() -> Element {
+    let mut expanded = use_signal(|| false);
+    let mut entries = use_signal(Vec::<LogEntry>::new);
+    let mut min_level = use_signal(|| Level::Info);
+    // Call id to show, or every record when empty
+    let mut call_filter = use_signal(String::new);
+    let mut status = use_signal(|| None::<String>);
+
+    // The log lives outside Dioxus; re-read it while the pane is open
+    use_future(move || async move {
+        loop {
+            if *expanded.peek() {
+                entries.set(logging::entries());
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+
+    let level = *min_level.read();
+    let call_value = call_filter.read().clone();
+    let (calls, rows) = {
+        let entries = entries.read();
+        let mut calls: Vec<String> = Vec::new();
+        for entry in entries.iter().rev() {
+            if let Some(id) = &entry.call_id {
+                if !calls.contains(id) {
+                    calls.push(id.clone());
+                }
+            }
+        }
+        let matching: Vec<LogEntry> = entries
+            .iter()
+            .filter(|e| e.level <= level)
+            .filter(|e| call_value.is_empty() || e.call_id.as_deref() == Some(call_value.as_str()))
+            .cloned()
+            .collect();
+        let skip = matching.len().saturating_sub(MAX_ROWS);
+        (calls, matching.into_iter().skip(skip).collect::<Vec<_>>())
+    };
+    let json_lines = rows
+        .iter()
+        .map(|entry| LogEntry { message: redact(&entry.message), ..entry.clone() }.to_json())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    rsx! {
+        div {
+            class: "bg-white rounded-xl px-6 py-3 shadow-sm border border-gray-200",
+            div {
+                class: "flex justify-between items-center",
+                button {
+                    class: "text-sm text-gray-600 hover:text-gray-800",
+                    r#type: "button",
+                    onclick: move |_| {
+                        let open = *expanded.read();
+                        if !open {
+                            entries.set(logging::entries());
+                        }
+                        expanded.set(!open);
+                    },
+                    if *expanded.read() { "▾ Log" } else { "▸ Log" }
+                }
+                if *expanded.read() {
+                    button {
+                        class: "text-xs text-gray-500 hover:text-gray-700 disabled:text-gray-300",
+                        disabled: rows.is_empty(),
+                        title: "Copy the shown records as JSON lines (passwords removed)",
+                        onclick: move |_| {
+                            let eval = document::eval("navigator.clipboard.writeText(await dioxus.recv());");
+                            let copied = eval.send(json_lines.clone()).is_ok();
+                            status.set(Some(if copied { "Copied".to_string() } else { "Copy failed".to_string() }));
+                        },
+                        "Copy JSON"
+                    }
+                }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "mt-3 flex flex-col gap-3",
+                    div {
+                        class: "flex gap-3",
+                        select {
+                            class: "px-3 py-2 border border-gray-300 rounded-md text-xs bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            oninput: move |evt| {
+                                if let Ok(level) = evt.value().parse::<Level>() {
+                                    min_level.set(level);
+                                }
+                            },
+                            for option_level in [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace] {
+                                option {
+                                    value: "{option_level}",
+                                    selected: option_level == level,
+                                    "{option_level} and above"
+                                }
+                            }
+                        }
+                        select {
+                            class: "flex-1 min-w-0 px-3 py-2 border border-gray-300 rounded-md text-xs font-mono bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            oninput: move |evt| call_filter.set(evt.value()),
+                            option { value: "", selected: call_value.is_empty(), "All calls" }
+                            for id in calls {
+                                option {
+                                    value: "{id}",
+                                    selected: call_value == id,
+                                    "{id}"
+                                }
+                            }
+                        }
+                    }
+                    if let Some(message) = status.read().as_ref() {
+                        p { class: "text-xs text-gray-500", "{message}" }
+                    }
+
+                    if rows.is_empty() {
+                        p { class: "text-sm text-gray-500", "No log records match." }
+                    } else {
+                        div {
+                            class: "max-h-80 overflow-auto p-2 bg-gray-900 rounded-md text-xs font-mono",
+                            for (index, entry) in rows.iter().enumerate() {
+                                {
+                                    let time = entry.at.with_timezone(&chrono::Local).format("%H:%M:%S%.3f").to_string();
+                                    let color = match entry.level {
+                                        Level::Error => "text-red-400",
+                                        Level::Warn => "text-yellow-300",
+                                        Level::Info => "text-gray-100",
+                                        _ => "text-gray-400",
+                                    };
+                                    let level_label = format!("{:<5}", entry.level);
+                                    let call = entry.call_id.as_deref().map(|id| format!(" [{}]", id)).unwrap_or_default();
+                                    rsx! {
+                                        div {
+                                            key: "{index}",
+                                            class: "whitespace-pre-wrap break-all {color}",
+                                            title: "{entry.target}",
+                                            "{time} {level_label}{call} {entry.message}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
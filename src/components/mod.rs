@@ -11,6 +11,20 @@ pub mod hook_status;
 pub mod transfer_dialog;
 pub mod dtmf_keypad;
 pub mod audio_panel;
+pub mod advanced_settings;
+pub mod dial_policy_notice;
+pub mod call_history_panel;
+pub mod call_options_panel;
+pub mod codec_settings;
+pub mod call_stats_panel;
+pub mod sip_trace_panel;
+pub mod save_capture_button;
+pub mod diagnostics_button;
+pub mod log_viewer_panel;
+pub mod recording_settings;
+pub mod playback_panel;
+pub mod hold_settings;
+pub mod ring_settings;
 
 pub use app::App;
 pub use registration_screen::RegistrationScreen;
@@ -22,4 +36,18 @@ pub use call_controls::CallControls;
 pub use hook_status::HookStatus;
 pub use transfer_dialog::TransferDialog;
 pub use dtmf_keypad::DtmfKeypad;
-pub use audio_panel::AudioPanel;
\ No newline at end of file
+pub use audio_panel::AudioPanel;
+pub use advanced_settings::AdvancedSettings;
+pub use dial_policy_notice::DialPolicyNotice;
+pub use call_history_panel::CallHistoryPanel;
+pub use call_options_panel::CallOptionsPanel;
+pub use codec_settings::CodecSettings;
+pub use call_stats_panel::CallStatsPanel;
+pub use sip_trace_panel::SipTracePanel;
+pub use save_capture_button::SaveCaptureButton;
+pub use diagnostics_button::DiagnosticsButton;
+pub use log_viewer_panel::LogViewerPanel;
+pub use recording_settings::RecordingSettings;
+pub use playback_panel::PlaybackPanel;
+pub use hold_settings::HoldSettings;
+pub use ring_settings::RingSettings;
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::commands::SipCommand;
+use crate::playback::{PlaybackMode, PlaybackProgress};
+
+fn mm_ss(duration: Duration) -> String {
+    format!("{:02}:{:02}", duration.as_secs() / 60, duration.as_secs() % 60)
+}
+
+/// In-call file playback: pick a WAV or Ogg Opus file and send it to the
+/// other party instead of, or on top of, the microphone.
+#[component]
+pub fn PlaybackPanel(
+    sip_coroutine: Coroutine<SipCommand>,
+    playback: Signal<Option<(String, PlaybackProgress)>>,
+    notice: Signal<Option<String>>,
+) -> Element {
+    let mut mode = use_signal(PlaybackMode::default);
+
+    rsx! {
+        div {
+            class: "mt-4",
+            p { class: "text-xs uppercase tracking-wide text-gray-400 mb-2", "Play file" }
+            if let Some((name, progress)) = playback.read().clone() {
+                {
+                    let percent = if progress.length.is_zero() {
+                        0.0
+                    } else {
+                        (progress.position.as_secs_f64() / progress.length.as_secs_f64() * 100.0).min(100.0)
+                    };
+                    rsx! {
+                        div {
+                            class: "flex items-center gap-3",
+                            div {
+                                class: "flex-1 min-w-0",
+                                div {
+                                    class: "flex justify-between text-xs text-gray-600 mb-1",
+                                    span { class: "truncate", "▶ {name}" }
+                                    span { class: "font-mono", "{mm_ss(progress.position)} / {mm_ss(progress.length)}" }
+                                }
+                                div {
+                                    class: "h-2 bg-gray-200 rounded-full overflow-hidden",
+                                    div {
+                                        class: "h-full bg-blue-500",
+                                        style: "width: {percent:.1}%",
+                                    }
+                                }
+                            }
+                            button {
+                                class: "px-4 py-2 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-lg text-sm font-medium transition-colors",
+                                onclick: move |_| sip_coroutine.send(SipCommand::StopPlayback),
+                                "■ Stop"
+                            }
+                        }
+                    }
+                }
+            } else {
+                div {
+                    class: "flex gap-3",
+                    select {
+                        class: "flex-1 px-3 py-2 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        oninput: move |evt| {
+                            mode.set(if evt.value() == "mix" { PlaybackMode::Mix } else { PlaybackMode::Replace });
+                        },
+                        option { value: "replace", selected: *mode.read() == PlaybackMode::Replace, "Instead of microphone" }
+                        option { value: "mix", selected: *mode.read() == PlaybackMode::Mix, "Mixed with microphone" }
+                    }
+                    button {
+                        class: "px-4 py-2 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-lg text-sm font-medium transition-colors",
+                        onclick: move |_| {
+                            let mode = *mode.read();
+                            spawn(async move {
+                                if let Some(file) = rfd::AsyncFileDialog::new()
+                                    .add_filter("Audio", &["wav", "opus", "ogg"])
+                                    .pick_file()
+                                    .await
+                                {
+                                    sip_coroutine.send(SipCommand::PlayFile { path: file.path().to_path_buf(), mode });
+                                }
+                            });
+                        },
+                        "Choose & play…"
+                    }
+                }
+            }
+            if let Some(message) = notice.read().as_ref() {
+                p { class: "mt-1 text-xs text-red-600", "{message}" }
+            }
+        }
+    }
+}
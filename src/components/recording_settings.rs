@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+use crate::recording::{RecordingChannels, RecordingFormat, RecordingPolicy};
+
+/// Collapsible "Recording" section of the registration form: the
+/// always-record policy, file format, channel layout, folder and consent
+/// announcement. Calls can also be recorded on demand from the call controls.
+#[component]
+pub fn RecordingSettings(mut recording: Signal<RecordingPolicy>, disabled: bool) -> Element {
+    let mut expanded = use_signal(|| false);
+
+    let policy = recording.read().clone();
+    let summary = format!(
+        "{}{}",
+        if policy.always_record { "always, " } else { "on demand, " },
+        policy.format.id()
+    );
+
+    rsx! {
+        div {
+            class: "border-t border-gray-200 pt-4",
+            button {
+                class: "text-sm font-medium text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                if *expanded.read() { "▾ Recording" } else { "▸ Recording ({summary})" }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "flex flex-col gap-4 mt-4",
+                    label {
+                        class: "inline-flex items-center gap-2 text-sm text-gray-700",
+                        input {
+                            r#type: "checkbox",
+                            checked: policy.always_record,
+                            disabled: disabled,
+                            oninput: move |evt| recording.write().always_record = evt.checked(),
+                        }
+                        "Record every call"
+                    }
+                    div {
+                        class: "flex gap-3",
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Format"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                disabled: disabled,
+                                oninput: move |evt| recording.write().format = RecordingFormat::from_id(&evt.value()),
+                                for format in RecordingFormat::all() {
+                                    option { value: format.id(), selected: policy.format == format, "{format.label()}" }
+                                }
+                            }
+                        }
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Channels"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                disabled: disabled,
+                                oninput: move |evt| recording.write().channels = RecordingChannels::from_id(&evt.value()),
+                                for channels in RecordingChannels::all() {
+                                    option { value: channels.id(), selected: policy.channels == channels, "{channels.label()}" }
+                                }
+                            }
+                        }
+                    }
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Folder"
+                        }
+                        div {
+                            class: "flex gap-3",
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                value: "{policy.directory.display()}",
+                                disabled: disabled,
+                                oninput: move |evt| recording.write().directory = PathBuf::from(evt.value()),
+                            }
+                            button {
+                                class: "px-4 py-3 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-md text-sm disabled:opacity-50",
+                                r#type: "button",
+                                disabled: disabled,
+                                onclick: move |_| {
+                                    spawn(async move {
+                                        if let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await {
+                                            recording.write().directory = folder.path().to_path_buf();
+                                        }
+                                    });
+                                },
+                                "Choose…"
+                            }
+                        }
+                    }
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Announcement"
+                        }
+                        div {
+                            class: "flex gap-3",
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "None (WAV file played when recording starts)",
+                                value: policy.announcement.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let value = evt.value();
+                                    recording.write().announcement = (!value.trim().is_empty()).then(|| PathBuf::from(value.trim()));
+                                },
+                            }
+                            button {
+                                class: "px-4 py-3 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-md text-sm disabled:opacity-50",
+                                r#type: "button",
+                                disabled: disabled,
+                                onclick: move |_| {
+                                    spawn(async move {
+                                        if let Some(file) = rfd::AsyncFileDialog::new().add_filter("WAV audio", &["wav"]).pick_file().await {
+                                            recording.write().announcement = Some(file.path().to_path_buf());
+                                        }
+                                    });
+                                },
+                                "Choose…"
+                            }
+                        }
+                        label {
+                            class: "mt-2 inline-flex items-center gap-2 text-sm text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: policy.announce_remote_only,
+                                disabled: disabled || policy.announcement.is_none(),
+                                oninput: move |evt| recording.write().announce_remote_only = evt.checked(),
+                            }
+                            "Play to the other party only"
+                        }
+                        p { class: "mt-1 text-xs text-gray-500", "Check the law where you and the other party are: many places require telling everyone on the call that it is recorded. With an announcement set, a call is only recorded if it could be played." }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,62 @@
+use std::path::PathBuf;
+
 use dioxus::prelude::*;
-use crate::sip_client::CallState;
+use crate::codecs::CodecPreferences;
+use crate::srtp::SrtpPolicy;
+use crate::recording::RecordingPolicy;
+use crate::tones::RingConfig;
+use crate::dial_plan::DialPlan;
+use crate::dial_policy::DialPolicy;
+use crate::sip_client::{CallState, ExtraHeaders, KeepaliveConfig, ModeSelection, PrivacyConfig, ProxyConfig};
 use crate::network_utils::get_available_interfaces;
+use crate::commands::SipCommand;
+use crate::components::{AdvancedSettings, CodecSettings, DiagnosticsButton, HoldSettings, RecordingSettings, RingSettings};
 
 #[component]
 pub fn RegistrationScreen(
     username: Signal<String>,
     password: Signal<String>,
     server_uri: Signal<String>,
+    mut connection_mode: Signal<ModeSelection>,
+    mut auth_username: Signal<String>,
+    mut sip_domain: Signal<String>,
+    realm: Signal<String>,
+    mut display_name: Signal<String>,
     mut selected_interface: Signal<Option<String>>,
     mut port: Signal<String>,
+    keepalive: Signal<KeepaliveConfig>,
+    proxy: Signal<ProxyConfig>,
+    dial_plan: Signal<DialPlan>,
+    dial_policy: Signal<DialPolicy>,
+    privacy: Signal<PrivacyConfig>,
+    extra_headers: Signal<ExtraHeaders>,
+    codecs: Signal<CodecPreferences>,
+    srtp: Signal<SrtpPolicy>,
+    recording: Signal<RecordingPolicy>,
+    music_on_hold: Signal<Option<PathBuf>>,
+    ringing: Signal<RingConfig>,
     registration_state: Signal<CallState>,
+    sip_coroutine: Coroutine<SipCommand>,
+    diagnostics_notice: Signal<Option<String>>,
     on_register: EventHandler<()>,
     on_skip: EventHandler<()>
 ) -> Element {
     let binding = registration_state.read();
     let server_uri_value = server_uri.read();
     
-    // Smart detection: if the URI contains @, it's P2P mode
-    let is_p2p_mode = server_uri_value.contains('@');
+    // Explicit mode, or (for Auto) inferred from the server field
+    let selected_mode = *connection_mode.read();
+    let mode = selected_mode.resolve(&server_uri_value);
+    let is_p2p_mode = mode == ModeSelection::PeerToPeer;
+    let is_receiver_mode = mode == ModeSelection::Receiver;
+    let is_server_mode = mode == ModeSelection::Server;
+    let validation = selected_mode.validate(&username.read(), &server_uri_value);
+    let can_register = validation.is_ok();
+    let server_label = match selected_mode {
+        ModeSelection::Server => "SIP Server",
+        ModeSelection::PeerToPeer => "Peer Address",
+        _ => "SIP Server (optional)",
+    };
     
     // Get available network interfaces
     let interfaces = get_available_interfaces();
@@ -27,7 +66,7 @@ pub fn RegistrationScreen(
             "Ready to configure"
         },
         CallState::Registering => {
-            if server_uri_value.is_empty() {
+            if is_receiver_mode {
                 "Starting listener..."
             } else if is_p2p_mode {
                 "Connecting to peer..."
@@ -36,7 +75,7 @@ pub fn RegistrationScreen(
             }
         },
         CallState::Registered => {
-            if server_uri_value.is_empty() {
+            if is_receiver_mode {
                 "Listening for incoming calls"
             } else {
                 "Connected successfully"
@@ -109,34 +148,62 @@ pub fn RegistrationScreen(
                     }
                 }
                 
-                // SIP Server field (optional)
+                // Connection mode selector
                 div {
                     label {
                         class: "block text-sm font-medium text-gray-700 mb-2",
-                        "SIP Server (optional)"
+                        "Connection Mode"
                     }
-                    input {
-                        class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
-                        r#type: "text",
-                        placeholder: "sip.example.com",
-                        value: "{server_uri}",
-                        oninput: move |evt| server_uri.set(evt.value()),
-                        disabled: is_loading
+                    select {
+                        class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                        disabled: is_loading,
+                        oninput: move |evt| {
+                            connection_mode.set(match evt.value().as_str() {
+                                "server" => ModeSelection::Server,
+                                "p2p" => ModeSelection::PeerToPeer,
+                                "receiver" => ModeSelection::Receiver,
+                                _ => ModeSelection::Auto,
+                            });
+                        },
+                        option { value: "auto", selected: selected_mode == ModeSelection::Auto, "Auto (detect from address)" }
+                        option { value: "server", selected: selected_mode == ModeSelection::Server, "Register with SIP server" }
+                        option { value: "p2p", selected: selected_mode == ModeSelection::PeerToPeer, "Peer-to-peer" }
+                        option { value: "receiver", selected: selected_mode == ModeSelection::Receiver, "Receive calls only" }
                     }
-                    p {
-                        class: "text-xs text-gray-600 mt-1",
-                        if server_uri_value.is_empty() {
-                            "Listen for incoming calls only"
-                        } else if is_p2p_mode {
-                            "Direct peer-to-peer connection"
-                        } else {
-                            "Connect to SIP server"
+                }
+                
+                // SIP Server / peer address field (hidden for an explicit receiver)
+                if selected_mode != ModeSelection::Receiver {
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "{server_label}"
+                        }
+                        input {
+                            class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: if selected_mode == ModeSelection::PeerToPeer { "bob@192.168.1.20:5060" } else { "sip.example.com" },
+                            value: "{server_uri}",
+                            oninput: move |evt| server_uri.set(evt.value()),
+                            disabled: is_loading
+                        }
+                        if selected_mode == ModeSelection::Auto {
+                            p {
+                                class: "text-xs text-gray-600 mt-1",
+                                if is_receiver_mode {
+                                    "Listen for incoming calls only"
+                                } else if is_p2p_mode {
+                                    "Direct peer-to-peer connection"
+                                } else {
+                                    "Connect to SIP server"
+                                }
+                            }
                         }
                     }
                 }
                 
                 // Password field - only shown for server mode
-                if !is_p2p_mode && !server_uri_value.is_empty() {
+                if is_server_mode {
                     div {
                         label {
                             class: "block text-sm font-medium text-gray-700 mb-2",
@@ -151,6 +218,57 @@ pub fn RegistrationScreen(
                             disabled: is_loading
                         }
                     }
+                    
+                    // Auth ID and domain row - for PBXs where they differ from the extension/host
+                    div {
+                        class: "flex gap-3",
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Auth ID (optional)"
+                            }
+                            input {
+                                class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Same as name",
+                                value: "{auth_username}",
+                                oninput: move |evt| auth_username.set(evt.value()),
+                                disabled: is_loading
+                            }
+                        }
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Domain (optional)"
+                            }
+                            input {
+                                class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Same as server",
+                                value: "{sip_domain}",
+                                oninput: move |evt| sip_domain.set(evt.value()),
+                                disabled: is_loading
+                            }
+                        }
+                    }
+                    
+                    // Display name shown to the people we call
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Display Name (optional)"
+                        }
+                        input {
+                            class: "w-full px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                            r#type: "text",
+                            placeholder: "Alice Smith",
+                            value: "{display_name}",
+                            oninput: move |evt| display_name.set(evt.value()),
+                            disabled: is_loading
+                        }
+                    }
                 }
                 
                 // Network interface and Port row
@@ -200,27 +318,74 @@ pub fn RegistrationScreen(
                         }
                     }
                 }
+                
+                // Registration-only settings (keepalives, outbound proxy, dial plan, ...)
+                if is_server_mode {
+                    AdvancedSettings {
+                        realm: realm,
+                        keepalive: keepalive,
+                        proxy: proxy,
+                        dial_plan: dial_plan,
+                        dial_policy: dial_policy,
+                        privacy: privacy,
+                        extra_headers: extra_headers,
+                        disabled: is_loading,
+                    }
+                }
+
+                // Media settings apply to every mode
+                CodecSettings {
+                    codecs: codecs,
+                    srtp: srtp,
+                    disabled: is_loading,
+                }
+                RecordingSettings {
+                    recording: recording,
+                    disabled: is_loading,
+                }
+                HoldSettings {
+                    music_on_hold: music_on_hold,
+                    disabled: is_loading,
+                }
+                RingSettings {
+                    ringing: ringing,
+                    disabled: is_loading,
+                }
             }
             
             // Button container - right justified
             div {
-                class: "flex justify-end",
+                class: "flex justify-end items-center gap-4",
+                if let Err(hint) = &validation {
+                    if !username.read().is_empty() {
+                        span {
+                            class: "text-xs text-gray-500",
+                            "{hint}"
+                        }
+                    }
+                }
                 button {
                     class: {
                         let class_str = if is_loading { 
                             "px-8 py-3 bg-gray-400 text-white rounded-md text-sm font-medium cursor-not-allowed"
-                        } else if username.read().is_empty() {
+                        } else if !can_register {
                             "px-8 py-3 bg-gray-300 text-gray-500 rounded-md text-sm font-medium cursor-not-allowed"
                         } else {
                             "px-8 py-3 bg-slate-800 hover:bg-slate-700 text-white rounded-md text-sm font-medium cursor-pointer transition-colors"
                         };
                         class_str
                     },
-                    onclick: move |_| if !is_loading && !username.read().is_empty() { on_register.call(()) },
-                    disabled: is_loading || username.read().is_empty(),
+                    onclick: move |_| if !is_loading && can_register { on_register.call(()) },
+                    disabled: is_loading || !can_register,
                     if is_loading { "Connecting..." } else { "Login" }
                 }
             }
+
+            // For support tickets when login keeps failing
+            DiagnosticsButton {
+                sip_coroutine: sip_coroutine,
+                notice: diagnostics_notice,
+            }
         }
     }
 }
\ No newline at end of file
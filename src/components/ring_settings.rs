@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+use crate::audio::{list_devices, AudioDirection};
+use crate::tones::{RingConfig, RingbackLocale};
+
+/// Collapsible "Ringing" section of the registration form: ringtone file
+/// and device for incoming calls, ringback tone style for outgoing ones.
+#[component]
+pub fn RingSettings(mut ringing: Signal<RingConfig>, disabled: bool) -> Element {
+    let mut expanded = use_signal(|| false);
+    // Enumerated once (cpal device list is not reactive).
+    let output_devices = use_memo(|| list_devices(AudioDirection::Output));
+
+    let config = ringing.read().clone();
+    let summary = config
+        .ringtone
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "built-in".to_string());
+
+    rsx! {
+        div {
+            class: "border-t border-gray-200 pt-4",
+            button {
+                class: "text-sm font-medium text-gray-600 hover:text-gray-800",
+                r#type: "button",
+                onclick: move |_| {
+                    let open = *expanded.read();
+                    expanded.set(!open);
+                },
+                if *expanded.read() { "▾ Ringing" } else { "▸ Ringing ({summary}, {config.ringback.id()} ringback)" }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "flex flex-col gap-4 mt-4",
+                    div {
+                        label {
+                            class: "block text-sm font-medium text-gray-700 mb-2",
+                            "Ringtone"
+                        }
+                        div {
+                            class: "flex gap-3",
+                            input {
+                                class: "flex-1 min-w-0 px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                r#type: "text",
+                                placeholder: "Built-in (or a WAV / Ogg Opus file)",
+                                value: config.ringtone.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let value = evt.value();
+                                    ringing.write().ringtone = (!value.trim().is_empty()).then(|| PathBuf::from(value.trim()));
+                                },
+                            }
+                            button {
+                                class: "px-4 py-3 bg-gray-200 hover:bg-gray-300 text-gray-800 rounded-md text-sm disabled:opacity-50",
+                                r#type: "button",
+                                disabled: disabled,
+                                onclick: move |_| {
+                                    spawn(async move {
+                                        if let Some(file) = rfd::AsyncFileDialog::new()
+                                            .add_filter("Audio", &["wav", "opus", "ogg"])
+                                            .pick_file()
+                                            .await
+                                        {
+                                            ringing.write().ringtone = Some(file.path().to_path_buf());
+                                        }
+                                    });
+                                },
+                                "Choose…"
+                            }
+                        }
+                    }
+                    div {
+                        class: "flex gap-3",
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Ring on"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                disabled: disabled,
+                                oninput: move |evt| {
+                                    let value = evt.value();
+                                    ringing.write().device = (!value.is_empty()).then_some(value);
+                                },
+                                option { value: "", selected: config.device.is_none(), "System default" }
+                                for (id, name) in output_devices.read().iter() {
+                                    option { key: "{id}", value: "{id}", selected: config.device == Some(id.to_string()), "{name}" }
+                                }
+                            }
+                        }
+                        div {
+                            class: "flex-1",
+                            label {
+                                class: "block text-sm font-medium text-gray-700 mb-2",
+                                "Ringback tone"
+                            }
+                            select {
+                                class: "w-full h-[46px] px-4 py-3 border border-gray-300 rounded-md text-sm bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent disabled:bg-gray-100 disabled:cursor-not-allowed",
+                                disabled: disabled,
+                                oninput: move |evt| ringing.write().ringback = RingbackLocale::from_id(&evt.value()),
+                                for locale in RingbackLocale::all() {
+                                    option { value: locale.id(), selected: config.ringback == locale, "{locale.label()}" }
+                                }
+                            }
+                        }
+                    }
+                    p { class: "text-xs text-gray-500", "The ringback tone plays while the other phone rings, unless the network sends its own audio." }
+                }
+            }
+        }
+    }
+}
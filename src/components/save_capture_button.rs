@@ -0,0 +1,44 @@
+use dioxus::prelude::*;
+use crate::commands::SipCommand;
+
+/// "Save capture…" link for the current call: asks where to write the
+/// .pcapng, then has the coroutine export the call's SIP (and RTP).
+#[component]
+pub fn SaveCaptureButton(
+    call_id: String,
+    sip_coroutine: Coroutine<SipCommand>,
+    mut notice: Signal<Option<String>>,
+) -> Element {
+    rsx! {
+        div {
+            class: "flex justify-end items-center gap-3 text-xs",
+            if let Some(message) = notice.read().as_ref() {
+                span { class: "text-gray-500 truncate", title: "{message}", "{message}" }
+            }
+            button {
+                class: "text-gray-500 hover:text-gray-700 shrink-0",
+                r#type: "button",
+                onclick: move |_| {
+                    let call_id = call_id.clone();
+                    spawn(async move {
+                        let file_name = format!("call-{}.pcapng", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                        let Some(file) = rfd::AsyncFileDialog::new()
+                            .set_file_name(&file_name)
+                            .add_filter("pcapng", &["pcapng"])
+                            .save_file()
+                            .await
+                        else {
+                            return;
+                        };
+                        notice.set(None);
+                        sip_coroutine.send(SipCommand::SaveCapture {
+                            call_id,
+                            path: file.path().to_path_buf(),
+                        });
+                    });
+                },
+                "Save capture (.pcapng)…"
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use dioxus::prelude::*;
+use crate::commands::SipCommand;
+use crate::sip_client::CaptureOptions;
+use crate::sip_trace::SipTrace;
+
+/// Collapsible "SIP Trace" panel: the captured SIP messages as a ladder
+/// diagram, filterable by Call-ID, with the raw text of the selected message
+/// and copy / export to a text file. Also holds the pcap capture switches.
+#[component]
+pub fn SipTracePanel(
+    mut trace: Signal<SipTrace>,
+    mut capture_options: Signal<CaptureOptions>,
+    sip_coroutine: Coroutine<SipCommand>,
+) -> Element {
+    let mut expanded = use_signal(|| false);
+    // Call-ID to show, or every message when empty
+    let mut filter = use_signal(String::new);
+    let mut selected = use_signal(|| None::<usize>);
+    let mut status = use_signal(|| None::<String>);
+
+    let count = trace.read().len();
+    let toggle_label = if *expanded.read() {
+        "▾ SIP Trace".to_string()
+    } else {
+        format!("▸ SIP Trace ({} message{})", count, if count == 1 { "" } else { "s" })
+    };
+
+    let filter_value = filter.read().clone();
+    let call_id = (!filter_value.is_empty()).then_some(filter_value.as_str());
+    let (dialogs, ladder, raw_messages) = if *expanded.read() {
+        let trace = trace.read();
+        let raw: Vec<String> = trace.messages(call_id).map(|m| m.to_text()).collect();
+        (trace.dialogs(), trace.ladder(call_id), raw)
+    } else {
+        Default::default()
+    };
+    let columns = ladder.endpoints.len().max(2);
+    let center = move |column: usize| (column as f32 + 0.5) * 100.0 / columns as f32;
+    let selected_text = selected
+        .read()
+        .and_then(|index| raw_messages.get(index).cloned());
+
+    let export_text = move || {
+        let filter = filter.read().clone();
+        trace.read().to_text((!filter.is_empty()).then_some(filter.as_str()))
+    };
+
+    rsx! {
+        div {
+            class: "bg-white rounded-xl px-6 py-3 shadow-sm border border-gray-200",
+            div {
+                class: "flex justify-between items-center",
+                button {
+                    class: "text-sm text-gray-600 hover:text-gray-800",
+                    r#type: "button",
+                    onclick: move |_| {
+                        let open = *expanded.read();
+                        expanded.set(!open);
+                    },
+                    "{toggle_label}"
+                }
+                if *expanded.read() {
+                    div {
+                        class: "flex gap-3 text-xs",
+                        button {
+                            class: "text-gray-500 hover:text-gray-700 disabled:text-gray-300",
+                            disabled: count == 0,
+                            onclick: move |_| {
+                                let eval = document::eval("navigator.clipboard.writeText(await dioxus.recv());");
+                                let copied = eval.send(export_text()).is_ok();
+                                status.set(Some(if copied { "Copied".to_string() } else { "Copy failed".to_string() }));
+                            },
+                            "Copy"
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-gray-700 disabled:text-gray-300",
+                            disabled: count == 0,
+                            onclick: move |_| {
+                                let text = export_text();
+                                spawn(async move {
+                                    let file_name = format!("sip-trace-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+                                    let Some(file) = rfd::AsyncFileDialog::new()
+                                        .set_file_name(&file_name)
+                                        .add_filter("Text", &["txt"])
+                                        .save_file()
+                                        .await
+                                    else {
+                                        return;
+                                    };
+                                    let path = file.path().to_path_buf();
+                                    match tokio::fs::write(&path, text).await {
+                                        Ok(()) => status.set(Some(format!("Saved to {}", path.display()))),
+                                        Err(e) => {
+                                            log::error!("Failed to export SIP trace: {}", e);
+                                            status.set(Some(format!("Export failed: {}", e)));
+                                        }
+                                    }
+                                });
+                            },
+                            "Export…"
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-gray-700 disabled:text-gray-300",
+                            disabled: count == 0,
+                            onclick: move |_| {
+                                trace.write().clear();
+                                filter.set(String::new());
+                                selected.set(None);
+                                status.set(None);
+                            },
+                            "Clear"
+                        }
+                    }
+                }
+            }
+
+            if *expanded.read() {
+                div {
+                    class: "mt-3 flex flex-col gap-3",
+                    select {
+                        class: "w-full px-3 py-2 border border-gray-300 rounded-md text-xs font-mono bg-white text-gray-700 cursor-pointer focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        oninput: move |evt| {
+                            filter.set(evt.value());
+                            selected.set(None);
+                        },
+                        option { value: "", selected: filter_value.is_empty(), "All messages" }
+                        for (id, first_line) in dialogs {
+                            option {
+                                value: "{id}",
+                                selected: filter_value == id,
+                                "{first_line} — {id}"
+                            }
+                        }
+                    }
+                    if let Some(message) = status.read().as_ref() {
+                        p { class: "text-xs text-gray-500", "{message}" }
+                    }
+                    div {
+                        class: "flex gap-6",
+                        label {
+                            class: "inline-flex items-center gap-2 text-xs text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: capture_options.read().rtp,
+                                oninput: move |evt| {
+                                    capture_options.write().rtp = evt.checked();
+                                    sip_coroutine.send(SipCommand::SetCaptureOptions(capture_options.read().clone()));
+                                },
+                            }
+                            "Capture RTP for pcap export"
+                        }
+                        label {
+                            class: "inline-flex items-center gap-2 text-xs text-gray-700",
+                            input {
+                                r#type: "checkbox",
+                                checked: capture_options.read().save_failed_calls,
+                                oninput: move |evt| {
+                                    capture_options.write().save_failed_calls = evt.checked();
+                                    sip_coroutine.send(SipCommand::SetCaptureOptions(capture_options.read().clone()));
+                                },
+                            }
+                            "Save .pcapng for failed calls"
+                        }
+                    }
+
+                    if ladder.rows.is_empty() {
+                        p { class: "text-sm text-gray-500", "No SIP messages captured yet." }
+                    } else {
+                        div {
+                            class: "max-h-80 overflow-y-auto border border-gray-100 rounded-md",
+                            // Endpoint headers
+                            div {
+                                class: "sticky top-0 flex bg-gray-50 border-b border-gray-200 text-xs font-medium text-gray-600",
+                                div { class: "w-24 shrink-0" }
+                                div {
+                                    class: "flex flex-1",
+                                    for endpoint in ladder.endpoints.iter() {
+                                        div {
+                                            class: "flex-1 py-1 text-center truncate",
+                                            title: "{endpoint}",
+                                            "{endpoint}"
+                                        }
+                                    }
+                                }
+                            }
+                            for (index, row) in ladder.rows.iter().enumerate() {
+                                {
+                                    let (left, right) = (center(row.from.min(row.to)), center(row.from.max(row.to)));
+                                    let rightward = row.to > row.from;
+                                    let time = row.at.with_timezone(&chrono::Local).format("%H:%M:%S%.3f").to_string();
+                                    let is_selected = *selected.read() == Some(index);
+                                    let color = if row.is_request { "text-blue-700 border-blue-500" } else { "text-green-700 border-green-500" };
+                                    rsx! {
+                                        div {
+                                            key: "{index}",
+                                            class: if is_selected { "flex h-8 cursor-pointer bg-blue-50" } else { "flex h-8 cursor-pointer hover:bg-gray-50" },
+                                            onclick: move |_| {
+                                                let current = *selected.read();
+                                                selected.set(if current == Some(index) { None } else { Some(index) });
+                                            },
+                                            div { class: "w-24 shrink-0 px-2 text-xs font-mono text-gray-400 leading-8", "{time}" }
+                                            div {
+                                                class: "relative flex-1",
+                                                // Lifelines
+                                                for column in 0..columns {
+                                                    div {
+                                                        class: "absolute top-0 bottom-0 border-l border-gray-200",
+                                                        style: "left: {center(column)}%",
+                                                    }
+                                                }
+                                                // Arrow with its label
+                                                div {
+                                                    class: "absolute bottom-2 border-t-2 {color}",
+                                                    style: "left: {left}%; width: {right - left}%",
+                                                    span {
+                                                        class: "absolute -top-4 left-0 right-0 text-center text-xs font-mono truncate",
+                                                        "{row.label}"
+                                                    }
+                                                    span {
+                                                        class: if rightward { "absolute -top-[9px] -right-1 text-xs" } else { "absolute -top-[9px] -left-1 text-xs" },
+                                                        if rightward { "▶" } else { "◀" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(text) = selected_text {
+                        pre {
+                            class: "max-h-64 overflow-auto p-3 bg-gray-900 text-gray-100 text-xs rounded-md whitespace-pre",
+                            "{text}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@ use dioxus::prelude::*;
 pub fn UserInfoBar(
     username: String,
     status_text: String,
+    registrar_reachable: bool,
     on_logout: EventHandler<()>
 ) -> Element {
     rsx! {
@@ -19,6 +20,12 @@ pub fn UserInfoBar(
                     class: "text-gray-500 text-xs mt-0.5",
                     "{status_text}"
                 }
+                if !registrar_reachable {
+                    div {
+                        class: "inline-flex items-center mt-1 px-2 py-0.5 rounded-full text-xs font-medium bg-red-100 text-red-700",
+                        "Registrar unreachable"
+                    }
+                }
             }
             
             button {
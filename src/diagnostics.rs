@@ -0,0 +1,196 @@
+//! "Export diagnostics" bundle for support tickets: the configuration,
+//! this session's log, the SIP trace, recent calls, audio devices and
+//! network interfaces in one tar file, with credentials scrubbed.
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+
+use crate::audio::{list_devices, AudioDirection};
+use crate::call_history::CallHistory;
+use crate::logging::{self, LogEntry};
+use crate::network_utils::get_available_interfaces;
+use crate::sip_client::SipConfig;
+use crate::sip_trace::SipTrace;
+
+const REDACTED: &str = "<redacted>";
+
+/// Quoted secrets: digest `response="…"`, and `password: "…"` /
+/// `override_pin: Some("…")` fields in Debug-formatted config and commands.
+static QUOTED_SECRET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(\w*(?:password|passwd|secret|pin)|response)(\s*[:=]\s*(?:Some\()?)"[^"]*""#)
+        .expect("valid redaction pattern")
+});
+
+/// Unquoted digest `response=<hex>` (some UAs omit the quotes).
+static BARE_RESPONSE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(response\s*=\s*)[0-9a-f]+").expect("valid redaction pattern")
+});
+
+/// SDES master key and salt in SDP `a=crypto:` lines (RFC 4568
+/// `inline:<base64>|lifetime|MKI`); the lifetime and MKI are kept.
+static SDES_KEY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(inline:)[A-Za-z0-9+/=]+").expect("valid redaction pattern")
+});
+
+/// Scrub SIP digest responses, SRTP keys and password / PIN values from
+/// `text`.
+pub fn redact(text: &str) -> String {
+    let text = QUOTED_SECRET.replace_all(text, format!("${{1}}${{2}}\"{}\"", REDACTED));
+    let text = BARE_RESPONSE.replace_all(&text, format!("${{1}}{}", REDACTED));
+    SDES_KEY
+        .replace_all(&text, format!("${{1}}{}", REDACTED))
+        .into_owned()
+}
+
+/// Files of the bundle as `(name, contents)`, not yet redacted.
+pub fn collect(config: &SipConfig, trace: &SipTrace, history: &CallHistory) -> Vec<(&'static str, String)> {
+    let summary = format!(
+        "{} {}\nOS: {} ({})\nExported: {}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        Local::now().to_rfc3339(),
+    );
+
+    let entries = logging::entries();
+    let log = entries.iter().map(LogEntry::to_text).collect::<Vec<_>>().join("\n");
+    // Quotes are escaped in JSON, so scrub before serializing
+    let json_log = entries
+        .iter()
+        .map(|entry| LogEntry { message: redact(&entry.message), ..entry.clone() }.to_json())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let calls = history
+        .iter()
+        .map(|record| {
+            let mut line = format!(
+                "{} {} {} — {}",
+                record.at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                if record.is_incoming { "in " } else { "out" },
+                record.identity.label(),
+                record.outcome.label(),
+            );
+            if let Some(detail) = record.identity.detail() {
+                line.push_str(&format!(" ({})", detail));
+            }
+            if let Some(duration) = record.duration {
+                line.push_str(&format!(", {} s", duration.as_secs()));
+            }
+            if let Some(stats) = &record.stats {
+                line.push_str(&format!(", MOS {:.1}, {:.1}% loss", stats.mos(), stats.loss_percent()));
+            }
+            if let Some(path) = &record.capture {
+                line.push_str(&format!(", capture {}", path.display()));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut devices = String::new();
+    for (title, direction) in [("Input", AudioDirection::Input), ("Output", AudioDirection::Output)] {
+        devices.push_str(&format!("{} devices:\n", title));
+        for (id, name) in list_devices(direction) {
+            devices.push_str(&format!("  {} [{}]\n", name, id));
+        }
+    }
+
+    let interfaces = get_available_interfaces()
+        .iter()
+        .map(|interface| format!("{}\t{}", interface.name, interface.ip))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    vec![
+        ("summary.txt", summary),
+        ("config.txt", format!("{:#?}\n", config)),
+        ("session.log", log),
+        ("session.jsonl", json_log),
+        ("sip-trace.txt", trace.to_text(None)),
+        ("call-history.txt", calls),
+        ("audio-devices.txt", devices),
+        ("network-interfaces.txt", interfaces),
+    ]
+}
+
+/// Redact `files` and pack them into a tar archive under one top-level
+/// directory named after the export time.
+pub fn bundle(files: &[(&str, String)], at: DateTime<Local>) -> Vec<u8> {
+    let dir = format!("sip-client-diagnostics-{}", at.format("%Y%m%d-%H%M%S"));
+    let mtime = at.timestamp().max(0) as u64;
+    let mut out = Vec::new();
+    for (name, contents) in files {
+        tar_entry(&mut out, &format!("{}/{}", dir, name), redact(contents).as_bytes(), mtime);
+    }
+    // End of archive: two zero blocks
+    out.resize(out.len() + 1024, 0);
+    out
+}
+
+/// One regular file in POSIX ustar format. `path` must fit the 100-byte
+/// name field, which our fixed file names do.
+fn tar_entry(out: &mut Vec<u8>, path: &str, data: &[u8], mtime: u64) {
+    let mut header = [0u8; 512];
+    let name = path.as_bytes();
+    header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0"); // uid
+    header[116..124].copy_from_slice(b"0000000\0"); // gid
+    header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+    header[136..148].copy_from_slice(format!("{:011o}\0", mtime).as_bytes());
+    header[148..156].copy_from_slice(b"        "); // checksum counts as spaces
+    header[156] = b'0'; // regular file
+    header[257..265].copy_from_slice(b"ustar\x0000");
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    out.extend_from_slice(&header);
+    out.extend_from_slice(data);
+    out.resize(out.len().next_multiple_of(512), 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_digest_responses() {
+        let quoted = r#"Authorization: Digest username="200", realm="pbx", nonce="abc", response="6629fae49393a05397450978507c4ef1""#;
+        assert_eq!(
+            redact(quoted),
+            r#"Authorization: Digest username="200", realm="pbx", nonce="abc", response="<redacted>""#
+        );
+        let bare = "Authorization: Digest username=\"200\", response=6629fae49393a05397450978507c4ef1, algorithm=MD5";
+        assert_eq!(
+            redact(bare),
+            "Authorization: Digest username=\"200\", response=<redacted>, algorithm=MD5"
+        );
+    }
+
+    #[test]
+    fn redacts_debug_formatted_secrets() {
+        let config = r#"ConnectionMode::Server { username: "200", password: "hunter2", auth_username: None }"#;
+        assert_eq!(
+            redact(config),
+            r#"ConnectionMode::Server { username: "200", password: "<redacted>", auth_username: None }"#
+        );
+        let command = r#"MakeCall { target: "+4490", override_pin: Some("1234") }"#;
+        assert_eq!(redact(command), r#"MakeCall { target: "+4490", override_pin: Some("<redacted>") }"#);
+        assert_eq!(redact("override_pin: None"), "override_pin: None");
+    }
+
+    #[test]
+    fn redacts_sdes_keys() {
+        let sdp = "a=crypto:1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|2^20|1:32\r\n\
+                   a=crypto:2 AES_CM_128_HMAC_SHA1_32 INLINE:d0RmdmcmVCspeEc3QGZiNWpVLFJhQX1cfHAwJSoj\r\n";
+        assert_eq!(
+            redact(sdp),
+            "a=crypto:1 AES_CM_128_HMAC_SHA1_80 inline:<redacted>|2^20|1:32\r\n\
+             a=crypto:2 AES_CM_128_HMAC_SHA1_32 INLINE:<redacted>\r\n"
+        );
+    }
+}
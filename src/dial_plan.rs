@@ -0,0 +1,337 @@
+//! Dial-plan normalization of typed phone numbers.
+//!
+//! Turns `+1 (415) 555-0100`, `0044 20 7946 0000` or `9,555 0100` into a
+//! clean digit string, applies prefix rewrites (outside-line prefix,
+//! international access code, national trunk prefix, custom rules) and emits
+//! the result as `tel:`, `sip:...;user=phone` or plain `sip:`. Anything that
+//! is not a phone number (SIP URIs, `user@host`, named extensions) is left to
+//! [`sip_uri::dial_target`].
+
+use crate::sip_uri::{self, Scheme, SipUri, UriError};
+
+/// How global (`+...`) numbers are put on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// `sip:+14155550100@domain`
+    Sip,
+    /// `sip:+14155550100@domain;user=phone`
+    #[default]
+    UserPhone,
+    /// `tel:+14155550100`
+    Tel,
+}
+
+/// Replace a leading `prefix` with `replace` (e.g. `1800` → `+1800`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    pub prefix: String,
+    pub replace: String,
+}
+
+impl RewriteRule {
+    /// Parse a comma/newline separated `prefix=replacement` list, skipping
+    /// malformed entries.
+    pub fn parse_list(text: &str) -> Vec<Self> {
+        text.split([',', '\n'])
+            .filter_map(|rule| rule.split_once('='))
+            .map(|(prefix, replace)| Self {
+                prefix: prefix.trim().to_string(),
+                replace: replace.trim().to_string(),
+            })
+            .filter(|rule| !rule.prefix.is_empty())
+            .collect()
+    }
+
+    pub fn format_list(rules: &[Self]) -> String {
+        rules
+            .iter()
+            .map(|rule| format!("{}={}", rule.prefix, rule.replace))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialPlan {
+    /// Outside-line prefix stripped before a number (`9` in `9,5550100`).
+    pub outside_line: String,
+    /// International access code replaced by `+` (`00`, `011`).
+    pub international_prefix: String,
+    /// National trunk prefix (`0`) replaced by `+<country_code>`.
+    pub national_prefix: String,
+    /// Country calling code for national numbers; empty leaves them as-is.
+    pub country_code: String,
+    /// Custom rewrites, first match wins; applied after the outside-line
+    /// prefix is stripped and instead of the international and national
+    /// rewrites.
+    pub rules: Vec<RewriteRule>,
+    pub format: NumberFormat,
+}
+
+impl Default for DialPlan {
+    fn default() -> Self {
+        Self {
+            outside_line: String::new(),
+            international_prefix: "00".to_string(),
+            national_prefix: String::new(),
+            country_code: String::new(),
+            rules: Vec::new(),
+            format: NumberFormat::default(),
+        }
+    }
+}
+
+/// Numbers shorter than this without a `+` are treated as extensions, so an
+/// outside-line prefix is only stripped from them when written with a pause.
+const MIN_EXTERNAL_DIGITS: usize = 7;
+
+impl DialPlan {
+    /// Normalize a typed phone number to digits (with a leading `+` when it
+    /// became global), or `None` when `input` is not a phone number.
+    pub fn normalize(&self, input: &str) -> Option<String> {
+        let input = input.trim();
+        let input = match input.get(..4) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("tel:") => &input[4..],
+            _ => input,
+        };
+        if input.is_empty() || input.contains(['@', ':', ';']) {
+            return None;
+        }
+
+        // Characters people use to format numbers; ',' is a dial pause.
+        let paused = input.contains(',');
+        let (before_pause, after_pause) = input.split_once(',').unwrap_or(("", input));
+        let mut number: String = format!("{}{}", before_pause, after_pause)
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '(' | ')' | '-' | '.' | '/' | ','))
+            .collect();
+        let plus = number.starts_with('+');
+        if plus {
+            number.remove(0);
+        }
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '*' || c == '#') {
+            return None;
+        }
+        if plus {
+            return Some(format!("+{}", number));
+        }
+
+        let explicit = paused && before_pause.trim() == self.outside_line;
+        number = self.strip_outside_line(&number, explicit).to_string();
+
+        if let Some(rule) = self.rules.iter().find(|r| number.starts_with(&r.prefix)) {
+            return Some(format!("{}{}", rule.replace, &number[rule.prefix.len()..]));
+        }
+        if !self.international_prefix.is_empty() {
+            if let Some(rest) = number.strip_prefix(self.international_prefix.as_str()) {
+                if !rest.is_empty() {
+                    return Some(format!("+{}", rest));
+                }
+            }
+        }
+        if !self.national_prefix.is_empty() && !self.country_code.is_empty() {
+            if let Some(rest) = number.strip_prefix(self.national_prefix.as_str()) {
+                if rest.len() + 1 >= MIN_EXTERNAL_DIGITS {
+                    return Some(format!("+{}{}", self.country_code.trim_start_matches('+'), rest));
+                }
+            }
+        }
+        Some(number)
+    }
+
+    /// Strip the outside-line prefix from a digit string: always when
+    /// `explicit` (written with a pause), otherwise only from numbers long
+    /// enough to be external.
+    fn strip_outside_line<'a>(&self, number: &'a str, explicit: bool) -> &'a str {
+        if self.outside_line.is_empty() {
+            return number;
+        }
+        match number.strip_prefix(self.outside_line.as_str()) {
+            Some(rest) if explicit || rest.len() >= MIN_EXTERNAL_DIGITS => rest,
+            _ => number,
+        }
+    }
+
+    /// The number a request URI dials, for checking against the dialing
+    /// policy: the user part (the host when there is none), percent-decoded,
+    /// without `;` parameters or visual separators, and with the outside-line
+    /// prefix stripped and a leading international access code turned into
+    /// `+`, as the PBX would read them. Non-numeric user parts (`alice`) are
+    /// returned decoded but otherwise as written.
+    pub fn dialed_number(&self, uri: &SipUri) -> String {
+        let raw = uri.user.as_deref().unwrap_or(&uri.host);
+        let decoded = percent_decode(raw);
+        let number = decoded.split(';').next().unwrap_or_default();
+        let digits: String = number
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '(' | ')' | '-' | '.' | '/'))
+            .collect();
+        let body = digits.strip_prefix('+').unwrap_or(&digits);
+        if body.is_empty() || !body.chars().all(|c| c.is_ascii_digit() || c == '*' || c == '#') {
+            return decoded;
+        }
+        if digits.starts_with('+') {
+            return digits;
+        }
+        let digits = self.strip_outside_line(&digits, false);
+        match digits.strip_prefix(self.international_prefix.as_str()) {
+            Some(rest) if !self.international_prefix.is_empty() && !rest.is_empty() => format!("+{}", rest),
+            _ => digits.to_string(),
+        }
+    }
+
+    /// Request URI for a dial or transfer target. Global numbers are emitted
+    /// in the configured [`NumberFormat`]; other digit strings dial as
+    /// `sip:<digits>@<default_host>` like an extension.
+    pub fn target(&self, input: &str, default_host: Option<&str>) -> Result<SipUri, UriError> {
+        let Some(number) = self.normalize(input) else {
+            return sip_uri::dial_target(input, default_host);
+        };
+        if !number.starts_with('+') {
+            return sip_uri::dial_target(&number, default_host);
+        }
+        match (self.format, default_host) {
+            (NumberFormat::Tel, _) | (_, None) => Ok(SipUri::tel(number)),
+            (format, Some(host)) => {
+                let mut uri = sip_uri::dial_target(&number, Some(host))?;
+                if uri.scheme == Scheme::Tel {
+                    return Ok(uri);
+                }
+                if format == NumberFormat::UserPhone {
+                    uri.set_param("user", Some("phone"));
+                }
+                Ok(uri)
+            }
+        }
+    }
+}
+
+/// Decode `%XX` escapes (RFC 3986); malformed escapes are kept as written.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // from_str_radix would also take a sign (`%+1`)
+        let escaped = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> DialPlan {
+        DialPlan {
+            outside_line: "9".to_string(),
+            national_prefix: "0".to_string(),
+            country_code: "44".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn formatting_is_stripped() {
+        let plan = DialPlan::default();
+        assert_eq!(plan.normalize("+1 (415) 555-0100").as_deref(), Some("+14155550100"));
+        assert_eq!(plan.normalize("tel:+1.415.555.0100").as_deref(), Some("+14155550100"));
+        assert_eq!(plan.normalize("415/555 0100").as_deref(), Some("4155550100"));
+        assert_eq!(plan.normalize("*72#").as_deref(), Some("*72#"));
+        for input in ["", "alice", "sip:200@pbx", "200@pbx", "+", "555-CALL"] {
+            assert_eq!(plan.normalize(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn international_access_codes_become_plus() {
+        assert_eq!(plan().normalize("0044 20 7946 0000").as_deref(), Some("+442079460000"));
+        let plan = DialPlan {
+            international_prefix: "011".to_string(),
+            ..DialPlan::default()
+        };
+        assert_eq!(plan.normalize("011 44 20 7946 0000").as_deref(), Some("+442079460000"));
+        assert_eq!(plan.normalize("0044 20 7946 0000").as_deref(), Some("00442079460000"));
+    }
+
+    #[test]
+    fn outside_line_is_stripped_explicitly_or_from_long_numbers() {
+        let plan = plan();
+        // A pause always marks it
+        assert_eq!(plan.normalize("9,555 0100").as_deref(), Some("5550100"));
+        assert_eq!(plan.normalize("9,200").as_deref(), Some("200"));
+        // Without one, only numbers long enough to be external lose it
+        assert_eq!(plan.normalize("95550100").as_deref(), Some("5550100"));
+        assert_eq!(plan.normalize("9200").as_deref(), Some("9200"));
+        assert_eq!(plan.normalize("9 0044 20 7946 0000").as_deref(), Some("+442079460000"));
+    }
+
+    #[test]
+    fn national_numbers_get_the_country_code() {
+        let plan = plan();
+        assert_eq!(plan.normalize("020 7946 0000").as_deref(), Some("+442079460000"));
+        // Short codes keep their trunk prefix
+        assert_eq!(plan.normalize("0123").as_deref(), Some("0123"));
+        let plan = DialPlan {
+            country_code: String::new(),
+            ..plan
+        };
+        assert_eq!(plan.normalize("020 7946 0000").as_deref(), Some("02079460000"));
+    }
+
+    #[test]
+    fn custom_rules_apply_after_the_outside_line() {
+        let plan = DialPlan {
+            rules: RewriteRule::parse_list("1800=+1800, 00=+49, bad, =x"),
+            ..plan()
+        };
+        assert_eq!(plan.rules.len(), 2);
+        assert_eq!(RewriteRule::format_list(&plan.rules), "1800=+1800, 00=+49");
+        assert_eq!(plan.normalize("1800 555 0100").as_deref(), Some("+18005550100"));
+        assert_eq!(plan.normalize("9,1800 555 0100").as_deref(), Some("+18005550100"));
+        // First match wins over the international prefix
+        assert_eq!(plan.normalize("0030 1234567").as_deref(), Some("+49301234567"));
+    }
+
+    #[test]
+    fn global_numbers_use_the_configured_format() {
+        let target = |format, input: &str, host| {
+            let plan = DialPlan {
+                format,
+                ..plan()
+            };
+            plan.target(input, host).unwrap().to_string()
+        };
+        let number = "+1 415 555 0100";
+        assert_eq!(target(NumberFormat::Tel, number, Some("pbx")), "tel:+14155550100");
+        assert_eq!(target(NumberFormat::UserPhone, number, Some("pbx")), "sip:+14155550100@pbx;user=phone");
+        assert_eq!(target(NumberFormat::Sip, number, Some("pbx")), "sip:+14155550100@pbx");
+        // Without a host there is nothing to put after the @
+        assert_eq!(target(NumberFormat::Sip, number, None), "tel:+14155550100");
+        // Extensions dial as SIP whatever the format
+        assert_eq!(target(NumberFormat::Tel, "200", Some("pbx")), "sip:200@pbx");
+        assert_eq!(target(NumberFormat::Tel, "sip:alice@example.com", Some("pbx")), "sip:alice@example.com");
+    }
+
+    #[test]
+    fn percent_escapes_must_be_two_hex_digits() {
+        assert_eq!(percent_decode("%2B44%20"), "+44 ");
+        assert_eq!(percent_decode("%+1%-1%g0%4"), "%+1%-1%g0%4");
+        let plan = DialPlan::default();
+        assert_eq!(plan.dialed_number(&SipUri::parse("sip:%+1900@pbx").unwrap()), "%+1900");
+    }
+}
@@ -0,0 +1,274 @@
+//! Outgoing call restrictions.
+//!
+//! A [`DialPolicy`] sits in front of `SipClientManager::make_call` and
+//! `transfer` and refuses targets that match a deny rule (or, when an allow
+//! list is set, match none of it). Rules are checked against the number the
+//! request URI dials, as read by [`DialPlan::dialed_number`]: the user part
+//! (or the host when there is none), percent-decoded, without visual
+//! separators and with the international access code as `+`
+//! (`+442079460000`, `200`, `alice`), so a typed `sip:0044...@pbx` meets the
+//! same rules as `0044...`. A rule is either a prefix or, when written
+//! `re:<pattern>`, a regular expression. Built-in [`PolicyProfile`]s
+//! cover the common lab restrictions; the custom lists extend the chosen one.
+
+use regex::Regex;
+
+use crate::dial_plan::DialPlan;
+use crate::sip_uri::SipUri;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PolicyProfile {
+    /// No built-in restrictions.
+    #[default]
+    Unrestricted,
+    /// Block premium-rate and satellite/international-network numbers.
+    NoPremium,
+    /// Only numbers in the home country (dial plan country code) and
+    /// non-E.164 targets such as extensions; implies `NoPremium`.
+    NoInternational,
+}
+
+impl PolicyProfile {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unrestricted => "Unrestricted",
+            Self::NoPremium => "No premium numbers",
+            Self::NoInternational => "No international",
+        }
+    }
+
+    pub fn all() -> [Self; 3] {
+        [Self::Unrestricted, Self::NoPremium, Self::NoInternational]
+    }
+
+    /// Built-in (allow, deny) rules. `country_code` is the home country
+    /// calling code from the dial plan, without `+`.
+    fn rules(self, country_code: &str) -> (Vec<String>, Vec<String>) {
+        let premium = [
+            // NANP premium / pay-per-call
+            "+1900", "+1976",
+            // UK premium rate and revenue-share
+            "+4490", "+4491", "+4498", "+44871", "+44872", "+44873",
+            // International shared-cost, satellite and network codes
+            "+808", "+870", "+878", "+881", "+882", "+883", "+979",
+            // Premium numbers dialed nationally without a country code
+            // (full-length only, so short extensions like 1900 still work)
+            r"re:^1(900|976)\d{7}$", r"re:^09\d{6,}$",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+
+        match self {
+            Self::Unrestricted => (Vec::new(), Vec::new()),
+            Self::NoPremium => (Vec::new(), premium),
+            Self::NoInternational => {
+                // Non-E.164 targets (extensions, SIP names) plus the home country
+                let mut allow = vec!["re:^[^+]".to_string()];
+                if !country_code.is_empty() {
+                    allow.push(format!("+{}", country_code));
+                }
+                (allow, premium)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DialPolicy {
+    pub profile: PolicyProfile,
+    /// Extra allow rules; when the effective allow list is non-empty a
+    /// target must match one of them.
+    pub allow: Vec<String>,
+    /// Extra deny rules; a match always refuses the call.
+    pub deny: Vec<String>,
+    /// PIN that lets the user place a refused call once; `None` disables
+    /// overrides.
+    pub override_pin: Option<String>,
+}
+
+/// Why a call was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    pub number: String,
+    pub reason: String,
+}
+
+impl DialPolicy {
+    /// Check every rule compiles, so bad patterns are reported at login
+    /// rather than silently ignored on each call.
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in self.allow.iter().chain(&self.deny) {
+            if let Some(pattern) = rule.strip_prefix("re:") {
+                Regex::new(pattern).map_err(|e| format!("Invalid dialing rule '{}': {}", rule, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.profile == PolicyProfile::Unrestricted && self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Check a resolved request URI against the rules, reading the number
+    /// and home country code through `plan`.
+    pub fn check(&self, uri: &SipUri, plan: &DialPlan) -> Result<(), PolicyViolation> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let number = plan.dialed_number(uri);
+        let (mut allow, mut deny) = self.profile.rules(&plan.country_code);
+        allow.extend(self.allow.iter().cloned());
+        deny.extend(self.deny.iter().cloned());
+
+        if let Some(rule) = deny.iter().find(|rule| rule_matches(rule, &number)) {
+            return Err(PolicyViolation {
+                reason: format!(
+                    "{} is blocked by the dialing policy ({}, rule '{}')",
+                    number,
+                    self.profile.label(),
+                    rule
+                ),
+                number,
+            });
+        }
+        if !allow.is_empty() && !allow.iter().any(|rule| rule_matches(rule, &number)) {
+            return Err(PolicyViolation {
+                reason: format!(
+                    "{} is not permitted by the dialing policy ({})",
+                    number,
+                    self.profile.label()
+                ),
+                number,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether `pin` unlocks a refused call.
+    pub fn accepts_pin(&self, pin: &str) -> bool {
+        self.override_pin
+            .as_deref()
+            .is_some_and(|expected| !expected.is_empty() && expected == pin.trim())
+    }
+}
+
+/// Parse a rule list as typed in settings, one rule per line (commas are
+/// legal inside regular expressions).
+pub fn parse_rules(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn rule_matches(rule: &str, number: &str) -> bool {
+    match rule.strip_prefix("re:") {
+        // Invalid patterns are rejected by validate(); never match here.
+        Some(pattern) => Regex::new(pattern).is_ok_and(|re| re.is_match(number)),
+        None => number.starts_with(rule),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(country_code: &str) -> DialPlan {
+        DialPlan {
+            country_code: country_code.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn policy(profile: PolicyProfile) -> DialPolicy {
+        DialPolicy {
+            profile,
+            ..Default::default()
+        }
+    }
+
+    fn check(policy: &DialPolicy, uri: &str, plan: &DialPlan) -> Result<(), PolicyViolation> {
+        policy.check(&SipUri::parse(uri).unwrap(), plan)
+    }
+
+    #[test]
+    fn premium_numbers_are_refused_however_the_uri_is_written() {
+        let policy = policy(PolicyProfile::NoPremium);
+        let plan = plan("1");
+        for (uri, number) in [
+            ("sip:+19005550100@pbx", "+19005550100"),
+            ("sip:+1-900-555-0100@pbx", "+19005550100"),
+            ("sip:+1%20976%20555%200100@pbx", "+19765550100"),
+            ("sip:%2B1.900.555.0100@pbx", "+19005550100"),
+            ("sip:+1(900)5550100;isub=1@pbx", "+19005550100"),
+            ("sip:0019005550100@pbx", "+19005550100"),
+            ("sip:1-900-555-0100@pbx", "19005550100"),
+            ("sip:19005550100", "19005550100"),
+            ("tel:+1-900-555-0100", "+19005550100"),
+        ] {
+            let violation = check(&policy, uri, &plan).expect_err(uri);
+            assert_eq!(violation.number, number, "{}", uri);
+        }
+        assert!(check(&policy, "sip:1900@pbx", &plan).is_ok());
+        assert!(check(&policy, "sip:+14155550100@pbx", &plan).is_ok());
+    }
+
+    #[test]
+    fn no_international_reads_access_codes_in_uris() {
+        let policy = policy(PolicyProfile::NoInternational);
+        let plan = plan("1");
+        for uri in [
+            "sip:00442079460000@pbx",
+            "sip:0044-20-7946-0000@pbx;user=phone",
+            "sip:%2B442079460000@pbx",
+            "sip:00442079460000",
+            "tel:+44-20-7946-0000",
+        ] {
+            let violation = check(&policy, uri, &plan).expect_err(uri);
+            assert_eq!(violation.number, "+442079460000", "{}", uri);
+        }
+        for uri in ["sip:200@pbx", "sip:alice@pbx", "sip:+1-415-555-0100@pbx", "sip:4155550100@pbx"] {
+            assert!(check(&policy, uri, &plan).is_ok(), "{}", uri);
+        }
+
+        let plan = DialPlan {
+            international_prefix: "011".to_string(),
+            ..plan
+        };
+        assert!(check(&policy, "sip:011442079460000@pbx", &plan).is_err());
+    }
+
+    #[test]
+    fn outside_line_prefix_does_not_hide_the_dialed_number() {
+        let plan = DialPlan {
+            outside_line: "9".to_string(),
+            international_prefix: "011".to_string(),
+            ..plan("1")
+        };
+        let violation = check(&policy(PolicyProfile::NoInternational), "sip:9011442079460000@pbx", &plan).unwrap_err();
+        assert_eq!(violation.number, "+442079460000");
+        let violation = check(&policy(PolicyProfile::NoPremium), "sip:919005550100@pbx", &plan).unwrap_err();
+        assert_eq!(violation.number, "19005550100");
+        let violation = check(&policy(PolicyProfile::NoPremium), "sip:9-1-900-555-0100@pbx", &plan).unwrap_err();
+        assert_eq!(violation.number, "19005550100");
+
+        // Short extensions starting with the outside-line digit are left alone
+        assert!(check(&policy(PolicyProfile::NoInternational), "sip:9011@pbx", &plan).is_ok());
+        assert!(check(&policy(PolicyProfile::NoInternational), "sip:94155550100@pbx", &plan).is_ok());
+    }
+
+    #[test]
+    fn custom_rules_see_the_dialed_number() {
+        let policy = DialPolicy {
+            deny: vec!["+44".to_string(), r"re:^evil".to_string()],
+            ..Default::default()
+        };
+        let plan = plan("1");
+        assert!(check(&policy, "sip:0044%2020%207946%200000@pbx", &plan).is_err());
+        assert!(check(&policy, "sip:evil.example.com", &plan).is_err());
+        assert!(check(&policy, "sip:ev%69l@pbx", &plan).is_err());
+        assert!(check(&policy, "sip:alice@evil.example.com", &plan).is_ok());
+    }
+}
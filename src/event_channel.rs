@@ -6,6 +6,11 @@
 //! [`SipEvent`] and forwards it to the coroutine over an mpsc channel.
 
 use crate::audio::AudioDirection;
+use crate::call_stats::MediaStats;
+use crate::caller_id::CallerIdentity;
+use crate::codecs::NegotiatedMedia;
+use crate::playback::PlaybackProgress;
+use crate::sip_trace::SipMessage;
 
 /// A SIP event, already translated into terms the UI cares about.
 ///
@@ -17,13 +22,28 @@ pub enum SipEvent {
     /// Inbound INVITE — the phone is ringing.
     IncomingCall {
         call_id: String,
+        /// Raw `From` header value
         from: String,
-        display_name: Option<String>,
+        /// Identity to show (P-Asserted-Identity / Remote-Party-ID / From,
+        /// with privacy applied)
+        caller: CallerIdentity,
+        /// Whether the INVITE's SDP offers SRTP; `None` for an offerless
+        /// INVITE (the check then happens once media is negotiated).
+        srtp_offered: Option<bool>,
     },
-    /// Outbound call received a provisional 180/183 (remote is ringing).
+    /// Outbound call received a provisional 180/183 without SDP (remote is
+    /// ringing).
     Ringing { call_id: String },
+    /// Outbound call received a provisional response with SDP: early media
+    /// (e.g. a carrier announcement) is on its way.
+    EarlyMedia { call_id: String },
     /// Call answered / media established.
     Connected { call_id: String },
+    /// SDP offer/answer settled on a codec (re-sent after a re-INVITE
+    /// changes it).
+    MediaNegotiated { call_id: String, media: NegotiatedMedia },
+    /// RTCP report received; cumulative stats for the call so far.
+    MediaStats { call_id: String, stats: MediaStats },
     /// Call ended normally (BYE) or was cancelled.
     Ended { call_id: String, reason: String },
     /// Call failed (4xx/5xx/timeout).
@@ -32,10 +52,10 @@ pub enum SipEvent {
         code: u16,
         reason: String,
     },
-    /// Call placed on hold (local or remote).
-    OnHold { call_id: String },
-    /// Call resumed from hold (local or remote).
-    Resumed { call_id: String },
+    /// Call placed on hold, by us or (`remote`) by the other party.
+    OnHold { call_id: String, remote: bool },
+    /// Call resumed from hold, by us or (`remote`) by the other party.
+    Resumed { call_id: String, remote: bool },
     /// Local mute state changed.
     Muted { call_id: String, muted: bool },
     /// DTMF digit received from the remote party.
@@ -52,12 +72,59 @@ pub enum SipEvent {
         refer_to: String,
         attended: bool,
     },
-    /// Registration with the registrar succeeded.
-    Registered { registrar: String },
-    /// Registration failed.
-    RegistrationFailed { registrar: String, reason: String },
+    /// Registration with the registrar succeeded. `service_route` is the
+    /// Service-Route header list from the 200 OK (RFC 3608), possibly empty.
+    Registered {
+        registrar: String,
+        service_route: Vec<String>,
+    },
+    /// Registration failed. `code` is the final SIP status, or `None` for a
+    /// timeout / transport error.
+    RegistrationFailed {
+        registrar: String,
+        code: Option<u16>,
+        reason: String,
+    },
+    /// OPTIONS keepalives to the registrar stopped (or resumed) being answered.
+    RegistrarReachability { registrar: String, reachable: bool },
     /// Audio level update for VU meters (computed locally from PCM frames).
     AudioLevel { direction: AudioDirection, level: f32 },
+    /// Progress of a file played into the call; `None` once it (or the
+    /// recording announcement) has ended or was stopped.
+    Playback { name: String, progress: Option<PlaybackProgress> },
+    /// A SIP request/response was sent or received (for the trace viewer).
+    Trace(SipMessage),
     /// A non-call-specific error.
     Error { message: String },
 }
+
+impl SipEvent {
+    /// The call this event is about, if any.
+    pub fn call_id(&self) -> Option<&str> {
+        match self {
+            Self::IncomingCall { call_id, .. }
+            | Self::Ringing { call_id }
+            | Self::EarlyMedia { call_id }
+            | Self::Connected { call_id }
+            | Self::MediaNegotiated { call_id, .. }
+            | Self::MediaStats { call_id, .. }
+            | Self::Ended { call_id, .. }
+            | Self::Failed { call_id, .. }
+            | Self::OnHold { call_id, .. }
+            | Self::Resumed { call_id, .. }
+            | Self::Muted { call_id, .. }
+            | Self::Dtmf { call_id, .. }
+            | Self::TransferProgress { call_id, .. }
+            | Self::TransferCompleted { call_id }
+            | Self::TransferFailed { call_id, .. }
+            | Self::ReferRequested { call_id, .. } => Some(call_id),
+            Self::Registered { .. }
+            | Self::RegistrationFailed { .. }
+            | Self::RegistrarReachability { .. }
+            | Self::AudioLevel { .. }
+            | Self::Playback { .. }
+            | Self::Trace(_)
+            | Self::Error { .. } => None,
+        }
+    }
+}
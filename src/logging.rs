@@ -0,0 +1,212 @@
+//! Structured session log: every record is tagged with the account and,
+//! while a call is up, its call id and direction, so one call can be
+//! followed across the SIP client, the UI coroutine and the audio bridge.
+//!
+//! Records go to stderr as text or, with `SIP_CLIENT_LOG_FORMAT=json`, as
+//! JSON lines, and the most recent ones are kept in memory for the in-app
+//! log viewer and the diagnostics bundle.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use log::Level;
+
+/// Entries kept in memory; older ones are dropped.
+const MAX_ENTRIES: usize = 5000;
+
+/// Environment variable selecting the stderr format (`text` or `json`).
+const FORMAT_ENV: &str = "SIP_CLIENT_LOG_FORMAT";
+
+static ENTRIES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static CONTEXT: Mutex<LogContext> = Mutex::new(LogContext { account: None, call: None });
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallDirection {
+    Inbound,
+    Outbound,
+}
+
+impl CallDirection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Inbound => "inbound",
+            Self::Outbound => "outbound",
+        }
+    }
+}
+
+/// Fields attached to every record logged while they are set.
+#[derive(Debug, Clone)]
+struct LogContext {
+    account: Option<String>,
+    call: Option<(String, CallDirection)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    fn from_env() -> Self {
+        match std::env::var(FORMAT_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub at: DateTime<Utc>,
+    pub level: Level,
+    /// Module path of the emitter, e.g. `sip_client::sip_client` or `rvoip_dialog_core::...`.
+    pub target: String,
+    pub message: String,
+    pub account: Option<String>,
+    pub call_id: Option<String>,
+    pub direction: Option<CallDirection>,
+}
+
+impl LogEntry {
+    fn capture(record: &log::Record) -> Self {
+        let context = CONTEXT.lock().map(|c| c.clone()).ok();
+        let (account, call) = context.map(|c| (c.account, c.call)).unwrap_or_default();
+        Self {
+            at: Utc::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            account,
+            call_id: call.as_ref().map(|(id, _)| id.clone()),
+            direction: call.map(|(_, direction)| direction),
+        }
+    }
+
+    /// `2024-05-01T10:00:00.000Z INFO  target [call_id=… direction=… account=…]: message`
+    pub fn to_text(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(call_id) = &self.call_id {
+            fields.push(format!("call_id={}", call_id));
+        }
+        if let Some(direction) = self.direction {
+            fields.push(format!("direction={}", direction.as_str()));
+        }
+        if let Some(account) = &self.account {
+            fields.push(format!("account={}", account));
+        }
+        let fields = if fields.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", fields.join(" "))
+        };
+        format!(
+            "{} {:<5} {}{}: {}",
+            self.at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            self.level,
+            self.target,
+            fields,
+            self.message
+        )
+    }
+
+    /// One JSON object; unset fields are omitted.
+    pub fn to_json(&self) -> String {
+        let mut object = serde_json::Map::new();
+        object.insert(
+            "ts".into(),
+            self.at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true).into(),
+        );
+        object.insert("level".into(), self.level.as_str().into());
+        object.insert("target".into(), self.target.clone().into());
+        if let Some(call_id) = &self.call_id {
+            object.insert("call_id".into(), call_id.clone().into());
+        }
+        if let Some(direction) = self.direction {
+            object.insert("direction".into(), direction.as_str().into());
+        }
+        if let Some(account) = &self.account {
+            object.insert("account".into(), account.clone().into());
+        }
+        object.insert("message".into(), self.message.clone().into());
+        serde_json::Value::Object(object).to_string()
+    }
+
+    pub fn render(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Text => self.to_text(),
+            LogFormat::Json => self.to_json(),
+        }
+    }
+}
+
+/// Filters with env_logger (`RUST_LOG`) and keeps a copy of every record
+/// that passes for the viewer.
+struct SessionLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for SessionLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        if let Ok(mut entries) = ENTRIES.lock() {
+            if entries.len() == MAX_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(LogEntry::capture(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install `builder`'s filter as the global logger, writing structured
+/// records to stderr and keeping them for the session.
+pub fn init(mut builder: env_logger::Builder) {
+    let format = LogFormat::from_env();
+    builder.format(move |buf, record| writeln!(buf, "{}", LogEntry::capture(record).render(format)));
+    let inner = builder.build();
+    let max_level = inner.filter();
+    log::set_boxed_logger(Box::new(SessionLogger { inner }))
+        .expect("logger initialized twice");
+    log::set_max_level(max_level);
+}
+
+/// Account tag for subsequent records (e.g. `alice@pbx.example.com`).
+pub fn set_account(account: Option<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.account = account;
+    }
+}
+
+/// Account records are currently tagged with.
+pub fn account() -> Option<String> {
+    CONTEXT.lock().ok().and_then(|context| context.account.clone())
+}
+
+/// Call tag for subsequent records; `None` once no call is up.
+pub fn set_call(call: Option<(String, CallDirection)>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.call = call;
+    }
+}
+
+/// Snapshot of the kept records, oldest first.
+pub fn entries() -> Vec<LogEntry> {
+    ENTRIES
+        .lock()
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
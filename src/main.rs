@@ -4,19 +4,36 @@ mod sip_client;
 mod components;
 mod event_channel;
 mod network_utils;
+mod sip_resolver;
+mod sip_uri;
+mod sip_headers;
+mod sip_trace;
+mod pcap;
+mod srtp;
+mod dial_plan;
+mod dial_policy;
+mod caller_id;
+mod call_history;
+mod call_stats;
+mod codecs;
 mod commands;
 mod audio;
+mod diagnostics;
+mod logging;
+mod playback;
+mod recording;
+mod tones;
 
 use components::App;
 
 fn main() {
     // Initialize logging. Default to a quiet filter so rvoip's per-packet DEBUG
     // firehose doesn't starve the real-time audio threads. RUST_LOG overrides
-    // this (e.g. `RUST_LOG=debug` for diagnostics).
-    env_logger::Builder::from_env(
+    // this (e.g. `RUST_LOG=debug` for diagnostics). Records are tagged with
+    // the account and current call, and kept for the in-app log viewer.
+    logging::init(env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or("warn,sip_client=info"),
-    )
-    .init();
+    ));
     
     info!("Starting SIP Client");
     
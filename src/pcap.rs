@@ -0,0 +1,197 @@
+//! pcapng export of the SIP and RTP a call exchanged, for Wireshark.
+//!
+//! We never see the packets the OS sends, only the payloads rvoip hands us,
+//! so each one is wrapped in synthesized IPv4/IPv6 and UDP headers and
+//! written with `LINKTYPE_RAW`. SIP that really travelled over TCP/TLS is
+//! written as UDP too (already decrypted), with a packet comment saying so.
+
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+use chrono::{DateTime, Utc};
+
+/// Raw IPv4/IPv6 packets, no link-layer header.
+const LINKTYPE_RAW: u16 = 101;
+const IPPROTO_UDP: u8 = 17;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_INTERFACE: u32 = 0x0000_0001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x0000_0006;
+
+const OPT_END: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+
+/// One UDP payload and the addresses it travelled between.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedPacket {
+    pub at: DateTime<Utc>,
+    pub src: SocketAddr,
+    pub dst: SocketAddr,
+    pub payload: Vec<u8>,
+    /// Shown by Wireshark as the packet comment.
+    pub comment: Option<String>,
+}
+
+/// Serialize `packets` (in capture order) as a pcapng file. `comment` is
+/// stored on the section header (e.g. the call it belongs to).
+pub fn pcapng(packets: &[CapturedPacket], comment: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    let mut options = Vec::new();
+    push_option(&mut options, OPT_COMMENT, comment.as_bytes());
+    push_option(
+        &mut options,
+        OPT_SHB_USERAPPL,
+        concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")).as_bytes(),
+    );
+    push_option(&mut options, OPT_END, &[]);
+    let mut body = Vec::new();
+    body.extend_from_slice(&0x1A2B_3C4Du32.to_le_bytes()); // byte-order magic
+    body.extend_from_slice(&1u16.to_le_bytes()); // major
+    body.extend_from_slice(&0u16.to_le_bytes()); // minor
+    body.extend_from_slice(&(-1i64).to_le_bytes()); // section length unknown
+    body.extend_from_slice(&options);
+    push_block(&mut out, BLOCK_SECTION_HEADER, &body);
+
+    // One interface; timestamps default to microsecond resolution
+    let mut body = Vec::new();
+    body.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes()); // reserved
+    body.extend_from_slice(&0u32.to_le_bytes()); // no snaplen
+    push_option(&mut body, OPT_IF_NAME, b"sip-client");
+    push_option(&mut body, OPT_END, &[]);
+    push_block(&mut out, BLOCK_INTERFACE, &body);
+
+    for packet in packets {
+        let data = ip_udp_packet(packet.src, packet.dst, &packet.payload);
+        let micros = packet.at.timestamp_micros().max(0) as u64;
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u32.to_le_bytes()); // interface id
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes()); // captured
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes()); // original
+        body.extend_from_slice(&data);
+        pad4(&mut body);
+        if let Some(comment) = &packet.comment {
+            push_option(&mut body, OPT_COMMENT, comment.as_bytes());
+            push_option(&mut body, OPT_END, &[]);
+        }
+        push_block(&mut out, BLOCK_ENHANCED_PACKET, &body);
+    }
+    out
+}
+
+/// `payload` in a UDP datagram inside an IPv4 or IPv6 packet. Mixed
+/// families are written as IPv6 with the IPv4 side mapped (`::ffff:a.b.c.d`).
+fn ip_udp_packet(src: SocketAddr, dst: SocketAddr, payload: &[u8]) -> Vec<u8> {
+    let udp_len = 8 + payload.len();
+    let mut udp = Vec::with_capacity(udp_len);
+    udp.extend_from_slice(&src.port().to_be_bytes());
+    udp.extend_from_slice(&dst.port().to_be_bytes());
+    udp.extend_from_slice(&(udp_len as u16).to_be_bytes());
+    udp.extend_from_slice(&[0, 0]); // checksum, filled below
+    udp.extend_from_slice(payload);
+
+    match (src.ip(), dst.ip()) {
+        (IpAddr::V4(s), IpAddr::V4(d)) => {
+            let mut pseudo = Vec::with_capacity(12);
+            pseudo.extend_from_slice(&s.octets());
+            pseudo.extend_from_slice(&d.octets());
+            pseudo.extend_from_slice(&[0, IPPROTO_UDP]);
+            pseudo.extend_from_slice(&(udp_len as u16).to_be_bytes());
+            set_udp_checksum(&mut udp, &pseudo);
+
+            let mut ip = Vec::with_capacity(20 + udp_len);
+            ip.push(0x45); // v4, 20-byte header
+            ip.push(0);
+            ip.extend_from_slice(&((20 + udp_len) as u16).to_be_bytes());
+            ip.extend_from_slice(&[0, 0, 0x40, 0]); // id 0, don't fragment
+            ip.push(64); // ttl
+            ip.push(IPPROTO_UDP);
+            ip.extend_from_slice(&[0, 0]);
+            ip.extend_from_slice(&s.octets());
+            ip.extend_from_slice(&d.octets());
+            let checksum = internet_checksum(&[&ip]);
+            ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+            ip.extend_from_slice(&udp);
+            ip
+        }
+        (s, d) => {
+            let (s, d) = (to_v6(s), to_v6(d));
+            let mut pseudo = Vec::with_capacity(40);
+            pseudo.extend_from_slice(&s.octets());
+            pseudo.extend_from_slice(&d.octets());
+            pseudo.extend_from_slice(&(udp_len as u32).to_be_bytes());
+            pseudo.extend_from_slice(&[0, 0, 0, IPPROTO_UDP]);
+            set_udp_checksum(&mut udp, &pseudo);
+
+            let mut ip = Vec::with_capacity(40 + udp_len);
+            ip.extend_from_slice(&[0x60, 0, 0, 0]); // v6, no traffic class/flow
+            ip.extend_from_slice(&(udp_len as u16).to_be_bytes());
+            ip.push(IPPROTO_UDP);
+            ip.push(64); // hop limit
+            ip.extend_from_slice(&s.octets());
+            ip.extend_from_slice(&d.octets());
+            ip.extend_from_slice(&udp);
+            ip
+        }
+    }
+}
+
+fn to_v6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+        IpAddr::V6(v6) => v6,
+    }
+}
+
+fn set_udp_checksum(udp: &mut [u8], pseudo_header: &[u8]) {
+    // 0 means "no checksum" on the wire, so a computed 0 is sent as 0xFFFF
+    let checksum = match internet_checksum(&[pseudo_header, udp]) {
+        0 => 0xFFFF,
+        sum => sum,
+    };
+    udp[6..8].copy_from_slice(&checksum.to_be_bytes());
+}
+
+/// RFC 1071 one's-complement checksum over the concatenated `parts`.
+fn internet_checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum = 0u32;
+    let mut odd: Option<u8> = None;
+    for byte in parts.iter().flat_map(|p| p.iter().copied()) {
+        match odd.take() {
+            Some(high) => sum += u32::from(u16::from_be_bytes([high, byte])),
+            None => odd = Some(byte),
+        }
+    }
+    if let Some(high) = odd {
+        sum += u32::from(u16::from_be_bytes([high, 0]));
+    }
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn push_block(out: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let total = (12 + body.len()) as u32;
+    out.extend_from_slice(&block_type.to_le_bytes());
+    out.extend_from_slice(&total.to_le_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(&total.to_le_bytes());
+}
+
+fn push_option(out: &mut Vec<u8>, code: u16, value: &[u8]) {
+    out.extend_from_slice(&code.to_le_bytes());
+    out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    out.extend_from_slice(value);
+    pad4(out);
+}
+
+fn pad4(out: &mut Vec<u8>) {
+    while !out.len().is_multiple_of(4) {
+        out.push(0);
+    }
+}
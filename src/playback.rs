@@ -0,0 +1,422 @@
+//! Audio clips played into a call: recording notices, prompts, hold music.
+//!
+//! The audio bridge hands every outgoing (microphone) and incoming
+//! (speaker) frame to an [`AudioInjector`], which mixes the active clip
+//! into it or replaces it. Clips are decoded and resampled to the bridge's
+//! rate before they start, so the audio threads only copy samples.
+
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Opus always decodes to (and timestamps in) 48 kHz.
+const OPUS_RATE: u32 = 48_000;
+
+/// Sample rates and channel counts accepted from WAV headers; anything
+/// outside is a corrupt or hostile file.
+const WAV_RATES: std::ops::RangeInclusive<u32> = 1_000..=384_000;
+const WAV_MAX_CHANNELS: usize = 32;
+
+/// Mono 16-bit PCM, whole file in memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioClip {
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+impl AudioClip {
+    /// Decode a WAV (16-bit PCM or 32-bit float) or Ogg Opus file, any
+    /// channel count, mixed down to mono.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        if data.starts_with(b"OggS") {
+            Self::from_ogg_opus(&data)
+        } else {
+            Self::from_wav(&data)
+        }
+    }
+
+    pub fn from_wav(data: &[u8]) -> io::Result<Self> {
+        let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err(invalid("not a WAV file"));
+        }
+        let mut format = None;
+        let mut offset = 12;
+        while offset + 8 <= data.len() {
+            let id = &data[offset..offset + 4];
+            let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            let body = &data[offset + 8..(offset + 8 + size).min(data.len())];
+            match id {
+                b"fmt " if body.len() >= 16 => {
+                    let tag = u16::from_le_bytes([body[0], body[1]]);
+                    let channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                    let sample_rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                    if !WAV_RATES.contains(&sample_rate) {
+                        return Err(invalid(&format!("unsupported sample rate {} Hz", sample_rate)));
+                    }
+                    if channels == 0 || channels > WAV_MAX_CHANNELS {
+                        return Err(invalid(&format!("unsupported channel count {}", channels)));
+                    }
+                    let bits = u16::from_le_bytes([body[14], body[15]]);
+                    // WAVE_FORMAT_EXTENSIBLE keeps the real tag in the sub-format GUID
+                    let tag = if tag == 0xFFFE && body.len() >= 26 {
+                        u16::from_le_bytes([body[24], body[25]])
+                    } else {
+                        tag
+                    };
+                    format = Some((tag, channels, sample_rate, bits));
+                }
+                b"data" => {
+                    let (tag, channels, sample_rate, bits) = format.ok_or_else(|| invalid("data before fmt chunk"))?;
+                    let frames: Vec<f32> = match (tag, bits) {
+                        (1, 16) => body
+                            .chunks_exact(2)
+                            .map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32768.0)
+                            .collect(),
+                        (3, 32) => body
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                            .collect(),
+                        _ => return Err(invalid("only 16-bit PCM and 32-bit float WAV are supported")),
+                    };
+                    let samples = frames
+                        .chunks(channels)
+                        .map(|frame| {
+                            let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+                            (mono * 32767.0).clamp(-32768.0, 32767.0) as i16
+                        })
+                        .collect();
+                    return Ok(Self { sample_rate, samples });
+                }
+                _ => {}
+            }
+            // Chunks are word-aligned
+            offset += 8 + size + size % 2;
+        }
+        Err(invalid("no audio data"))
+    }
+
+    /// Decode an Ogg Opus stream (RFC 7845) at 48 kHz, honouring the
+    /// header's pre-skip.
+    pub fn from_ogg_opus(data: &[u8]) -> io::Result<Self> {
+        let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let mut reader = ogg::reading::PacketReader::new(io::Cursor::new(data));
+        let read = |reader: &mut ogg::reading::PacketReader<_>| {
+            reader.read_packet().map_err(|e| invalid(format!("bad Ogg stream: {}", e)))
+        };
+
+        let head = read(&mut reader)?.ok_or_else(|| invalid("empty Ogg stream".into()))?;
+        let head = &head.data;
+        if head.len() < 19 || &head[0..8] != b"OpusHead" {
+            return Err(invalid("not an Ogg Opus file".into()));
+        }
+        let channels = match head[9] {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            n => return Err(invalid(format!("{} channel Opus is not supported", n))),
+        };
+        let channel_count = head[9] as usize;
+        let pre_skip = u16::from_le_bytes([head[10], head[11]]) as usize;
+        let mut decoder = opus::Decoder::new(OPUS_RATE, channels)
+            .map_err(|e| invalid(format!("Opus decoder: {}", e)))?;
+
+        // OpusTags
+        read(&mut reader)?;
+        // 120 ms, the longest an Opus packet can be
+        let mut pcm = vec![0i16; OPUS_RATE as usize * 120 / 1000 * channel_count];
+        let mut samples = Vec::new();
+        while let Some(packet) = read(&mut reader)? {
+            let frames = decoder
+                .decode(&packet.data, &mut pcm, false)
+                .map_err(|e| invalid(format!("bad Opus packet: {}", e)))?;
+            samples.extend(pcm[..frames * channel_count].chunks(channel_count).map(|frame| {
+                (frame.iter().map(|&s| i32::from(s)).sum::<i32>() / channel_count as i32) as i16
+            }));
+        }
+        samples.drain(..pre_skip.min(samples.len()));
+        Ok(Self { sample_rate: OPUS_RATE, samples })
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples.len() as f64 / f64::from(self.sample_rate.max(1)))
+    }
+
+    /// The clip at `sample_rate` (linear interpolation; prompts and hold
+    /// music don't need better for telephone audio).
+    pub fn resampled(&self, sample_rate: u32) -> Vec<i16> {
+        if sample_rate == self.sample_rate || self.samples.is_empty() {
+            return self.samples.clone();
+        }
+        let ratio = f64::from(self.sample_rate) / f64::from(sample_rate);
+        let len = (self.samples.len() as f64 / ratio) as usize;
+        (0..len)
+            .map(|i| {
+                let position = i as f64 * ratio;
+                let index = position as usize;
+                let next = self.samples.get(index + 1).copied().unwrap_or(self.samples[index]);
+                let fraction = position - index as f64;
+                (f64::from(self.samples[index]) * (1.0 - fraction) + f64::from(next) * fraction) as i16
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    /// The clip is sent instead of the microphone.
+    #[default]
+    Replace,
+    /// The clip is sent on top of the microphone.
+    Mix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlaybackOptions {
+    pub mode: PlaybackMode,
+    /// Also play the clip on our own speaker.
+    pub local: bool,
+    /// Start over at the end until stopped.
+    pub looped: bool,
+}
+
+/// Where a playback is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaybackProgress {
+    /// Which [`AudioInjector::play`] call this is about.
+    pub id: u64,
+    pub position: Duration,
+    pub length: Duration,
+    pub finished: bool,
+}
+
+#[derive(Debug)]
+struct Playback {
+    id: u64,
+    options: PlaybackOptions,
+    /// The clip, resampled to the bridge's rate by `play`.
+    sample_rate: u32,
+    samples: Vec<i16>,
+    length: Duration,
+    /// Next sample to send / to play locally.
+    send_position: usize,
+    local_position: usize,
+    finished: bool,
+}
+
+impl Playback {
+    /// Copy the next `frame.len()` samples from `position` into `frame`,
+    /// replacing or mixing. Returns the new position, or `None` at the end
+    /// of a non-looped clip. A frame at another rate than the clip was
+    /// prepared for is left alone.
+    fn fill(&self, frame: &mut [i16], sample_rate: u32, position: usize, mode: PlaybackMode) -> Option<usize> {
+        let looped = self.options.looped;
+        let samples = &self.samples;
+        if samples.is_empty() {
+            return None;
+        }
+        if sample_rate != self.sample_rate {
+            return Some(position);
+        }
+        let mut position = position;
+        for out in frame.iter_mut() {
+            if position >= samples.len() {
+                if !looped {
+                    if mode == PlaybackMode::Replace {
+                        *out = 0;
+                    }
+                    continue;
+                }
+                position = 0;
+            }
+            let sample = samples[position];
+            *out = match mode {
+                PlaybackMode::Replace => sample,
+                PlaybackMode::Mix => out.saturating_add(sample),
+            };
+            position += 1;
+        }
+        (looped || position < samples.len()).then_some(position)
+    }
+}
+
+/// Shared between the manager and the audio bridge's threads.
+#[derive(Debug, Clone, Default)]
+pub struct AudioInjector {
+    playback: Arc<Mutex<Option<Playback>>>,
+}
+
+impl AudioInjector {
+    /// Start `clip` on a bridge running at `sample_rate`, replacing whatever
+    /// was playing. Returns an id to tell this playback's
+    /// [`progress`](Self::progress) from a later one's.
+    pub fn play(&self, clip: AudioClip, options: PlaybackOptions, sample_rate: u32) -> u64 {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        // Resample here, not on the audio thread
+        let samples = clip.resampled(sample_rate);
+        if let Ok(mut playback) = self.playback.lock() {
+            *playback = Some(Playback {
+                id,
+                options,
+                sample_rate,
+                samples,
+                length: clip.duration(),
+                send_position: 0,
+                local_position: 0,
+                finished: false,
+            });
+        }
+        id
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut playback) = self.playback.lock() {
+            *playback = None;
+        }
+    }
+
+    /// Progress of the current clip; `None` when nothing was started since
+    /// the last [`stop`](Self::stop).
+    pub fn progress(&self) -> Option<PlaybackProgress> {
+        let playback = self.playback.lock().ok()?;
+        let playback = playback.as_ref()?;
+        let length = playback.length;
+        let position = if playback.finished {
+            length
+        } else {
+            Duration::from_secs_f64(playback.send_position as f64 / f64::from(playback.sample_rate.max(1)))
+        };
+        Some(PlaybackProgress {
+            id: playback.id,
+            position,
+            length,
+            finished: playback.finished,
+        })
+    }
+
+    /// Called by the bridge with each microphone frame before it is sent.
+    pub fn process_outgoing(&self, frame: &mut [i16], sample_rate: u32) {
+        let Ok(mut playback) = self.playback.lock() else {
+            return;
+        };
+        let Some(playback) = playback.as_mut().filter(|p| !p.finished) else {
+            return;
+        };
+        let mode = playback.options.mode;
+        match playback.fill(frame, sample_rate, playback.send_position, mode) {
+            Some(position) => playback.send_position = position,
+            None => playback.finished = true,
+        }
+    }
+
+    /// Called by the bridge with each frame before it is played locally.
+    pub fn process_incoming(&self, frame: &mut [i16], sample_rate: u32) {
+        let Ok(mut playback) = self.playback.lock() else {
+            return;
+        };
+        // Local playback ends with the sent one
+        let Some(playback) = playback.as_mut().filter(|p| p.options.local && !p.finished) else {
+            return;
+        };
+        if let Some(position) = playback.fill(frame, sample_rate, playback.local_position, PlaybackMode::Mix) {
+            playback.local_position = position;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.wrapping_mul(u32::from(channels) * 2).to_le_bytes());
+        wav.extend_from_slice(&channels.wrapping_mul(2).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        wav
+    }
+
+    #[test]
+    fn wav_headers_with_bad_rates_or_channels_are_rejected() {
+        for (channels, sample_rate) in [(1, 0), (1, u32::MAX), (0, 8000), (1000, 8000)] {
+            let err = AudioClip::from_wav(&wav(channels, sample_rate, &[0; 4])).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{} ch, {} Hz", channels, sample_rate);
+        }
+        let clip = AudioClip::from_wav(&wav(2, 8000, &[100, 300, 100, 300])).unwrap();
+        assert_eq!(clip.sample_rate, 8000);
+        assert_eq!(clip.samples.len(), 2);
+    }
+
+    /// An Ogg Opus stream of `packets` 20 ms CELT packets with no coded
+    /// audio (a bare TOC byte), which decode to silence.
+    fn ogg_opus(channels: u8, pre_skip: u16, packets: usize) -> Vec<u8> {
+        use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+        let mut out = Vec::new();
+        let mut writer = PacketWriter::new(&mut out);
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, channels]);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&8000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        writer.write_packet(head.into_boxed_slice(), 1, PacketWriteEndInfo::EndPage, 0).unwrap();
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&[0; 8]);
+        writer.write_packet(tags.into_boxed_slice(), 1, PacketWriteEndInfo::EndPage, 0).unwrap();
+        let toc = if channels == 2 { 0xFC } else { 0xF8 };
+        for i in 1..=packets {
+            let end = if i == packets { PacketWriteEndInfo::EndStream } else { PacketWriteEndInfo::NormalPacket };
+            writer.write_packet(Box::new([toc]), 1, end, (i * 960) as u64).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn ogg_opus_decodes_at_48khz_without_the_pre_skip() {
+        for channels in [1, 2] {
+            let clip = AudioClip::from_ogg_opus(&ogg_opus(channels, 312, 3)).unwrap();
+            assert_eq!(clip.sample_rate, 48_000);
+            assert_eq!(clip.samples.len(), 3 * 960 - 312, "{} channels", channels);
+        }
+        let err = AudioClip::from_ogg_opus(&ogg_opus(3, 312, 1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = AudioClip::from_ogg_opus(b"OggS").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn clips_are_resampled_when_played() {
+        let clip = AudioClip {
+            sample_rate: 8000,
+            samples: vec![1000; 8000],
+        };
+        let injector = AudioInjector::default();
+        injector.play(clip, PlaybackOptions::default(), 16_000);
+
+        // A frame at another rate is left alone
+        let mut frame = [0i16; 160];
+        injector.process_outgoing(&mut frame, 8000);
+        assert!(frame.iter().all(|&s| s == 0));
+
+        let mut frame = [0i16; 320];
+        injector.process_outgoing(&mut frame, 16_000);
+        assert!(frame.iter().all(|&s| s != 0));
+        let progress = injector.progress().unwrap();
+        assert_eq!(progress.position, Duration::from_millis(20));
+        assert_eq!(progress.length, Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,739 @@
+//! Call recording: both directions of the audio bridge, paired up on a
+//! writer thread and saved as WAV or Ogg Opus with the call's details as
+//! metadata.
+//!
+//! The audio threads only push frames into a channel through a
+//! [`RecordingTap`]; mixing, encoding and disk I/O happen on the writer, so
+//! a slow disk never glitches the call.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use crate::sip_uri::SipUri;
+
+/// How far one direction may run ahead before the other is padded with
+/// silence (hold, one-way audio, late first RTP packet).
+const MAX_SKEW_MS: usize = 500;
+
+/// Pre-skip in the Ogg Opus header: the libopus encoder lookahead at
+/// 48 kHz (6.5 ms) for the VoIP application.
+const OPUS_PRE_SKIP: u16 = 312;
+
+/// Largest Opus packet we ask the encoder for.
+const OPUS_MAX_PACKET: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingFormat {
+    #[default]
+    Wav,
+    OggOpus,
+}
+
+impl RecordingFormat {
+    pub fn all() -> [Self; 2] {
+        [Self::Wav, Self::OggOpus]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Wav => "WAV (uncompressed)",
+            Self::OggOpus => "Ogg Opus (compact)",
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::OggOpus => "opus",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "opus" => Self::OggOpus,
+            _ => Self::Wav,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        self.id()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingChannels {
+    /// Both parties mixed into one channel.
+    Mono,
+    /// Local party on the left channel, remote on the right.
+    #[default]
+    Stereo,
+}
+
+impl RecordingChannels {
+    pub fn all() -> [Self; 2] {
+        [Self::Stereo, Self::Mono]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mono => "Mono (mixed)",
+            Self::Stereo => "Stereo (me left, them right)",
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Mono => "mono",
+            Self::Stereo => "stereo",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "mono" => Self::Mono,
+            _ => Self::Stereo,
+        }
+    }
+
+    pub fn count(self) -> u16 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingPolicy {
+    /// Start recording every call as soon as its audio starts.
+    pub always_record: bool,
+    pub format: RecordingFormat,
+    pub channels: RecordingChannels,
+    /// Folder recordings are written to; created on first use.
+    pub directory: PathBuf,
+    /// Consent notice (WAV or Ogg Opus) sent to the call as each recording starts. If
+    /// it is set but cannot be played, the call is not recorded.
+    pub announcement: Option<PathBuf>,
+    /// Play the notice to the other party only, not on our own speaker.
+    pub announce_remote_only: bool,
+}
+
+impl Default for RecordingPolicy {
+    fn default() -> Self {
+        Self {
+            always_record: false,
+            format: RecordingFormat::default(),
+            channels: RecordingChannels::default(),
+            directory: default_directory(),
+            announcement: None,
+            announce_remote_only: false,
+        }
+    }
+}
+
+/// `~/SIP Recordings`, or under the temp dir when there is no home.
+pub fn default_directory() -> PathBuf {
+    std::env::home_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("SIP Recordings")
+}
+
+/// Details of the call stored in the file's tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingMetadata {
+    pub call_id: String,
+    /// Who the call is with, as shown in the UI.
+    pub remote: String,
+    /// Their address, when known and not withheld.
+    pub remote_uri: Option<SipUri>,
+    pub is_incoming: bool,
+    pub account: Option<String>,
+    pub started: DateTime<Local>,
+}
+
+impl RecordingMetadata {
+    fn title(&self) -> String {
+        format!("Call with {}", self.remote)
+    }
+
+    fn comment(&self) -> String {
+        let mut comment = format!(
+            "{} call {}",
+            if self.is_incoming { "Inbound" } else { "Outbound" },
+            self.call_id
+        );
+        if let Some(account) = &self.account {
+            comment.push_str(&format!(" on {}", account));
+        }
+        comment
+    }
+
+    /// `call-20240501-101500-alice_example.com.wav`, from the remote's
+    /// user and host, or their label when the address is unknown.
+    fn file_name(&self, format: RecordingFormat) -> String {
+        let remote = match &self.remote_uri {
+            Some(uri) => {
+                let user = uri.user.as_deref().map(|u| u.split(';').next().unwrap_or(u));
+                let host = Some(uri.host.as_str()).filter(|h| !h.is_empty());
+                user.into_iter().chain(host).collect::<Vec<_>>().join("@")
+            }
+            None => self.remote.clone(),
+        };
+        let remote: String = remote
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .take(40)
+            .collect();
+        format!(
+            "call-{}-{}.{}",
+            self.started.format("%Y%m%d-%H%M%S"),
+            remote,
+            format.extension()
+        )
+    }
+}
+
+/// 20 ms of audio from one direction of the bridge.
+#[derive(Debug, Clone)]
+pub struct TapFrame {
+    /// Microphone (what we send) rather than speaker (what we hear).
+    pub local: bool,
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+/// Handed to the audio bridge when it starts; forwards frames to the
+/// running [`Recorder`], if any, and drops them otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingTap {
+    sender: Arc<Mutex<Option<mpsc::Sender<TapFrame>>>>,
+}
+
+impl RecordingTap {
+    pub fn send(&self, frame: TapFrame) {
+        if let Ok(sender) = self.sender.lock() {
+            if let Some(sender) = sender.as_ref() {
+                let _ = sender.send(frame);
+            }
+        }
+    }
+
+    fn attach(&self, sender: mpsc::Sender<TapFrame>) {
+        if let Ok(mut slot) = self.sender.lock() {
+            *slot = Some(sender);
+        }
+    }
+
+    fn detach(&self) {
+        if let Ok(mut slot) = self.sender.lock() {
+            *slot = None;
+        }
+    }
+}
+
+/// A recording in progress.
+#[derive(Debug)]
+pub struct Recorder {
+    call_id: String,
+    path: PathBuf,
+    tap: RecordingTap,
+    writer: JoinHandle<io::Result<()>>,
+}
+
+impl Recorder {
+    /// Create the file under `policy.directory` and start taking frames
+    /// from `tap`.
+    pub fn start(tap: &RecordingTap, policy: &RecordingPolicy, metadata: RecordingMetadata) -> io::Result<Self> {
+        std::fs::create_dir_all(&policy.directory)?;
+        let path = policy.directory.join(metadata.file_name(policy.format));
+        let file = File::create(&path)?;
+        let (sender, frames) = mpsc::channel();
+        let (format, channels, call_id) = (policy.format, policy.channels, metadata.call_id.clone());
+        let writer = std::thread::Builder::new()
+            .name("call-recorder".to_string())
+            .spawn(move || write_recording(frames, file, format, channels, &metadata))?;
+        tap.attach(sender);
+        info!("Recording call {} to {}", call_id, path.display());
+        Ok(Self {
+            call_id,
+            path,
+            tap: tap.clone(),
+            writer,
+        })
+    }
+
+    pub fn call_id(&self) -> &str {
+        &self.call_id
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop taking frames and wait for the writer to finish the file (it
+    /// only has the last few frames and the header left to write).
+    pub fn finish(self) -> io::Result<PathBuf> {
+        self.tap.detach();
+        self.writer
+            .join()
+            .map_err(|_| io::Error::other("recording writer panicked"))??;
+        info!("Recording of call {} saved to {}", self.call_id, self.path.display());
+        Ok(self.path)
+    }
+}
+
+/// Open `path` with the system's default application.
+pub fn open(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+    command.arg(path).spawn().map(|_| ())
+}
+
+/// Writer thread: pair local and remote samples until the tap is detached,
+/// then finish the file.
+fn write_recording(
+    frames: mpsc::Receiver<TapFrame>,
+    file: File,
+    format: RecordingFormat,
+    channels: RecordingChannels,
+    metadata: &RecordingMetadata,
+) -> io::Result<()> {
+    let mut file = Some(file);
+    let mut sink: Option<Box<dyn SampleSink>> = None;
+    let mut sample_rate = 0;
+    let mut local = VecDeque::new();
+    let mut remote = VecDeque::new();
+
+    while let Ok(frame) = frames.recv() {
+        if sink.is_none() {
+            sample_rate = frame.sample_rate;
+            let file = file.take().expect("file is opened once");
+            sink = Some(open_sink(format, file, sample_rate, channels, metadata)?);
+        }
+        // Both directions run at the codec rate; anything else is a bridge bug
+        if frame.sample_rate != sample_rate {
+            warn!(
+                "Recording of call {}: dropped a {} Hz frame in a {} Hz recording",
+                metadata.call_id, frame.sample_rate, sample_rate
+            );
+            continue;
+        }
+        if frame.local {
+            local.extend(frame.samples);
+        } else {
+            remote.extend(frame.samples);
+        }
+        let max_skew = sample_rate as usize * MAX_SKEW_MS / 1000;
+        if let Some(sink) = sink.as_mut() {
+            sink.write(&pair(&mut local, &mut remote, Some(max_skew), channels))?;
+        }
+    }
+
+    // No audio ever arrived: still leave a valid (empty) file behind
+    let mut sink = match (sink, file) {
+        (Some(sink), _) => sink,
+        (None, Some(file)) => open_sink(format, file, 8000, channels, metadata)?,
+        (None, None) => unreachable!("the file goes into the sink"),
+    };
+    sink.write(&pair(&mut local, &mut remote, None, channels))?;
+    sink.finish()
+}
+
+/// Drain the samples both directions have in common, as interleaved
+/// (stereo) or mixed (mono) output. With `max_skew`, a side that is further
+/// ahead than that is matched with silence; with `None` (end of call)
+/// everything is drained.
+fn pair(
+    local: &mut VecDeque<i16>,
+    remote: &mut VecDeque<i16>,
+    max_skew: Option<usize>,
+    channels: RecordingChannels,
+) -> Vec<i16> {
+    let skew = max_skew.unwrap_or(0);
+    if local.len() > remote.len() + skew {
+        remote.resize(local.len() - skew, 0);
+    }
+    if remote.len() > local.len() + skew {
+        local.resize(remote.len() - skew, 0);
+    }
+    let n = local.len().min(remote.len());
+    let pairs = local.drain(..n).zip(remote.drain(..n));
+    match channels {
+        RecordingChannels::Stereo => pairs.flat_map(|(l, r)| [l, r]).collect(),
+        RecordingChannels::Mono => pairs
+            .map(|(l, r)| (i32::from(l) + i32::from(r)).clamp(i16::MIN.into(), i16::MAX.into()) as i16)
+            .collect(),
+    }
+}
+
+trait SampleSink {
+    /// Append interleaved samples.
+    fn write(&mut self, samples: &[i16]) -> io::Result<()>;
+    /// Flush buffered audio and complete the headers.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+fn open_sink(
+    format: RecordingFormat,
+    file: File,
+    sample_rate: u32,
+    channels: RecordingChannels,
+    metadata: &RecordingMetadata,
+) -> io::Result<Box<dyn SampleSink>> {
+    let out = BufWriter::new(file);
+    Ok(match format {
+        RecordingFormat::Wav => Box::new(WavSink::new(out, sample_rate, channels.count(), metadata)?),
+        RecordingFormat::OggOpus => Box::new(OggOpusSink::new(out, sample_rate, channels.count(), metadata)?),
+    })
+}
+
+/// 16-bit PCM WAV with a `LIST`/`INFO` chunk for the metadata. Sizes are
+/// patched in when the recording ends.
+struct WavSink<W: Write + Seek> {
+    out: W,
+    data_size_offset: u64,
+    data_len: u32,
+}
+
+impl<W: Write + Seek> WavSink<W> {
+    fn new(mut out: W, sample_rate: u32, channels: u16, metadata: &RecordingMetadata) -> io::Result<Self> {
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&0u32.to_le_bytes()); // patched in finish
+        header.extend_from_slice(b"WAVE");
+
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        header.extend_from_slice(&channels.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(sample_rate * u32::from(channels) * 2).to_le_bytes());
+        header.extend_from_slice(&(channels * 2).to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut info = b"INFO".to_vec();
+        for (id, value) in [
+            (b"INAM", metadata.title()),
+            (b"ICRD", metadata.started.format("%Y-%m-%d %H:%M:%S").to_string()),
+            (b"ICMT", metadata.comment()),
+            (b"ISFT", concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")).to_string()),
+        ] {
+            let mut text = value.into_bytes();
+            text.push(0);
+            info.extend_from_slice(id);
+            info.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            info.extend_from_slice(&text);
+            if text.len() % 2 == 1 {
+                info.push(0);
+            }
+        }
+        header.extend_from_slice(b"LIST");
+        header.extend_from_slice(&(info.len() as u32).to_le_bytes());
+        header.extend_from_slice(&info);
+
+        header.extend_from_slice(b"data");
+        let data_size_offset = header.len() as u64;
+        header.extend_from_slice(&0u32.to_le_bytes()); // patched in finish
+        out.write_all(&header)?;
+        Ok(Self {
+            out,
+            data_size_offset,
+            data_len: 0,
+        })
+    }
+}
+
+impl<W: Write + Seek> SampleSink for WavSink<W> {
+    fn write(&mut self, samples: &[i16]) -> io::Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.out.write_all(&bytes)?;
+        self.data_len = self.data_len.saturating_add(bytes.len() as u32);
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        // Everything after the RIFF size field; a saturated data size must
+        // not wrap it
+        let riff_size = (self.data_size_offset + 4 + u64::from(self.data_len) - 8).min(u64::from(u32::MAX)) as u32;
+        self.out.seek(SeekFrom::Start(4))?;
+        self.out.write_all(&riff_size.to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(self.data_size_offset))?;
+        self.out.write_all(&self.data_len.to_le_bytes())?;
+        self.out.flush()
+    }
+}
+
+/// Ogg Opus (RFC 7845) in 20 ms packets. The last packet is held back so
+/// it can carry the end-of-stream flag and the exact final granule.
+struct OggOpusSink<W: Write> {
+    writer: PacketWriter<W>,
+    encoder: opus::Encoder,
+    serial: u32,
+    channels: usize,
+    /// Samples per channel in one 20 ms packet at the input rate.
+    frame_len: usize,
+    pending: Vec<i16>,
+    /// Samples per channel encoded so far, in 48 kHz units.
+    encoded: u64,
+    held: Option<(Vec<u8>, u64)>,
+}
+
+impl<W: Write> OggOpusSink<W> {
+    fn new(out: W, sample_rate: u32, channels: u16, metadata: &RecordingMetadata) -> io::Result<Self> {
+        let opus_channels = if channels == 1 { opus::Channels::Mono } else { opus::Channels::Stereo };
+        let encoder = opus::Encoder::new(sample_rate, opus_channels, opus::Application::Voip)
+            .map_err(|e| io::Error::other(format!("Opus cannot encode {} Hz audio: {}", sample_rate, e)))?;
+        let mut sink = Self {
+            writer: PacketWriter::new(out),
+            encoder,
+            serial: rand::random(),
+            channels: usize::from(channels),
+            frame_len: sample_rate as usize / 50,
+            pending: Vec::new(),
+            encoded: 0,
+            held: None,
+        };
+
+        let mut head = b"OpusHead".to_vec();
+        head.push(1); // version
+        head.push(channels as u8);
+        head.extend_from_slice(&OPUS_PRE_SKIP.to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // mono/stereo mapping
+        sink.write_page(head)?;
+
+        let vendor = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+        let comments = [
+            format!("TITLE={}", metadata.title()),
+            format!("DATE={}", metadata.started.to_rfc3339()),
+            format!("COMMENT={}", metadata.comment()),
+        ];
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for comment in &comments {
+            tags.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            tags.extend_from_slice(comment.as_bytes());
+        }
+        sink.write_page(tags)?;
+        Ok(sink)
+    }
+
+    /// Header packets each get a page of their own.
+    fn write_page(&mut self, packet: Vec<u8>) -> io::Result<()> {
+        self.writer
+            .write_packet(packet.into_boxed_slice(), self.serial, PacketWriteEndInfo::EndPage, 0)
+    }
+
+    /// Encode one full packet of which `real` samples per channel are audio
+    /// (the rest is end-of-call padding).
+    fn encode(&mut self, samples: &[i16], real: usize) -> io::Result<()> {
+        let packet = self
+            .encoder
+            .encode_vec(samples, OPUS_MAX_PACKET)
+            .map_err(|e| io::Error::other(format!("Opus encoding failed: {}", e)))?;
+        // Granules count 48 kHz samples whatever the input rate
+        self.encoded += real as u64 * 48_000 / self.input_rate();
+        let granule = u64::from(OPUS_PRE_SKIP) + self.encoded;
+        if let Some((previous, previous_granule)) = self.held.replace((packet, granule)) {
+            self.writer.write_packet(
+                previous.into_boxed_slice(),
+                self.serial,
+                PacketWriteEndInfo::NormalPacket,
+                previous_granule,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn input_rate(&self) -> u64 {
+        self.frame_len as u64 * 50
+    }
+}
+
+impl<W: Write> SampleSink for OggOpusSink<W> {
+    fn write(&mut self, samples: &[i16]) -> io::Result<()> {
+        self.pending.extend_from_slice(samples);
+        let packet_len = self.frame_len * self.channels;
+        while self.pending.len() >= packet_len {
+            let packet: Vec<i16> = self.pending.drain(..packet_len).collect();
+            self.encode(&packet, self.frame_len)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        let packet_len = self.frame_len * self.channels;
+        // Pad the tail to a whole packet; an empty recording still gets one
+        // packet so the stream can be closed
+        if !self.pending.is_empty() || self.held.is_none() {
+            let real = self.pending.len() / self.channels;
+            let mut packet = std::mem::take(&mut self.pending);
+            packet.resize(packet_len, 0);
+            self.encode(&packet, real)?;
+        }
+        if let Some((last, granule)) = self.held.take() {
+            self.writer.write_packet(
+                last.into_boxed_slice(),
+                self.serial,
+                PacketWriteEndInfo::EndStream,
+                granule,
+            )?;
+        }
+        self.writer.into_inner().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// An in-memory file that stays readable after a sink consumes it.
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Cursor<Vec<u8>>>>);
+
+    impl Buffer {
+        fn bytes(&self) -> Vec<u8> {
+            self.0.borrow().get_ref().clone()
+        }
+    }
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for Buffer {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.borrow_mut().seek(pos)
+        }
+    }
+
+    fn metadata() -> RecordingMetadata {
+        RecordingMetadata {
+            call_id: "call-1".to_string(),
+            remote: "Alice".to_string(),
+            remote_uri: SipUri::parse("sip:alice@example.com").ok(),
+            is_incoming: true,
+            account: Some("bob@example.com".to_string()),
+            started: Local::now(),
+        }
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn a_side_running_ahead_is_padded_with_silence() {
+        let mut local: VecDeque<i16> = (1..=10).collect();
+        let mut remote = VecDeque::new();
+
+        // Within the skew nothing is written; beyond it the other side is padded
+        assert!(pair(&mut local, &mut remote, Some(10), RecordingChannels::Stereo).is_empty());
+        let paired = pair(&mut local, &mut remote, Some(4), RecordingChannels::Stereo);
+        assert_eq!(paired, [1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 6, 0]);
+        assert_eq!(local, [7, 8, 9, 10]);
+
+        remote.extend([100, 200]);
+        assert_eq!(pair(&mut local, &mut remote, Some(4), RecordingChannels::Mono), [107, 208]);
+
+        // At the end of the call everything left is drained
+        assert_eq!(pair(&mut local, &mut remote, None, RecordingChannels::Mono), [9, 10]);
+        assert!(local.is_empty() && remote.is_empty());
+
+        local.push_back(i16::MAX);
+        remote.push_back(i16::MAX);
+        assert_eq!(pair(&mut local, &mut remote, None, RecordingChannels::Mono), [i16::MAX]);
+    }
+
+    #[test]
+    fn wav_header_sizes_are_patched_on_finish() {
+        let buffer = Buffer::default();
+        let mut sink = Box::new(WavSink::new(buffer.clone(), 8000, 2, &metadata()).unwrap());
+        let data_size_offset = sink.data_size_offset as usize;
+        sink.write(&[1, -1, 2, -2]).unwrap();
+        sink.write(&[3, -3]).unwrap();
+        sink.finish().unwrap();
+
+        let bytes = buffer.bytes();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2);
+        assert_eq!(u32_at(&bytes, 24), 8000);
+        assert_eq!(u32_at(&bytes, 28), 8000 * 2 * 2);
+        assert_eq!(&bytes[data_size_offset - 4..data_size_offset], b"data");
+        assert_eq!(u32_at(&bytes, data_size_offset), 12);
+        assert_eq!(bytes.len(), data_size_offset + 4 + 12);
+    }
+
+    #[test]
+    fn wav_sizes_saturate_instead_of_wrapping() {
+        let buffer = Buffer::default();
+        let mut sink = Box::new(WavSink::new(buffer.clone(), 48_000, 2, &metadata()).unwrap());
+        sink.data_len = u32::MAX - 2;
+        sink.write(&[1, 2, 3]).unwrap();
+        let data_size_offset = sink.data_size_offset as usize;
+        sink.finish().unwrap();
+
+        let bytes = buffer.bytes();
+        assert_eq!(u32_at(&bytes, 4), u32::MAX);
+        assert_eq!(u32_at(&bytes, data_size_offset), u32::MAX);
+    }
+
+    #[test]
+    fn ogg_granules_count_48khz_samples_after_the_pre_skip() {
+        let buffer = Buffer::default();
+        let mut sink = Box::new(OggOpusSink::new(buffer.clone(), 8000, 1, &metadata()).unwrap());
+        // Three full 20 ms packets at 8 kHz and half of a fourth
+        sink.write(&[0; 160 * 3 + 80]).unwrap();
+        assert_eq!(sink.encoded, 3 * 960);
+        sink.finish().unwrap();
+
+        let mut reader = ogg::reading::PacketReader::new(Cursor::new(buffer.bytes()));
+        let head = reader.read_packet().unwrap().unwrap();
+        assert_eq!(&head.data[0..8], b"OpusHead");
+        assert_eq!(u16::from_le_bytes([head.data[10], head.data[11]]), OPUS_PRE_SKIP);
+        assert_eq!(u32_at(&head.data, 12), 8000);
+        let tags = reader.read_packet().unwrap().unwrap();
+        assert_eq!(&tags.data[0..8], b"OpusTags");
+
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.read_packet().unwrap() {
+            packets.push(packet);
+        }
+        assert_eq!(packets.len(), 4);
+        // The padded last packet only counts its real half
+        let last = packets.last().unwrap();
+        assert!(last.last_in_stream());
+        assert_eq!(last.absgp_page(), u64::from(OPUS_PRE_SKIP) + 3 * 960 + 480);
+    }
+}
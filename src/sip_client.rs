@@ -1,27 +1,55 @@
 use anyhow::{anyhow, Result};
-use log::{error, info};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
 use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
 
 // New rvoip (0.2.x) SIP surface. The old `rvoip::sip_client` module is gone;
 // everything below comes from `rvoip::sip` (re-exported `rvoip-sip`).
 use rvoip::sip::{
-    CallId, Config, Event, EventReceiver, PeerControl, RegistrationHandle, StreamPeer,
-    UnifiedCoordinator,
+    CallId, Config, Event, EventReceiver, PeerControl, RegistrationHandle, RtpCapture, SrtpMode,
+    StreamPeer, UnifiedCoordinator,
 };
 
-use crate::audio::{AudioBridge, AudioDirection, RunningAudio};
+use crate::audio::{AudioBridge, AudioDirection, BridgeHooks, CallFrames, OutputStream, RunningAudio, ToneOutput};
 use crate::event_channel::SipEvent;
+use crate::sip_resolver::{self, DnsResolver, SipTarget, StaticResolver, SystemResolver, Transport};
+use crate::call_stats::MediaStats;
+use crate::caller_id::{self, CallerIdentity};
+use crate::codecs::{Codec, CodecPreferences, NegotiatedMedia};
+use crate::pcap::CapturedPacket;
+use crate::playback::{AudioClip, AudioInjector, PlaybackMode, PlaybackOptions};
+use crate::recording::{Recorder, RecordingMetadata, RecordingPolicy, RecordingTap};
+use crate::tones::{RingConfig, ToneSource};
+use crate::commands::sip_commands::SipError;
+use crate::dial_plan::DialPlan;
+use crate::dial_policy::DialPolicy;
+use crate::logging;
+use crate::sip_headers::{self, Header};
+use crate::sip_trace::{SipMessage, TraceDirection};
+use crate::sip_uri::{self, Scheme, SipUri};
+use crate::srtp::{self, SrtpPolicy};
 
 #[derive(Debug, Clone)]
 pub enum ConnectionMode {
     Server {
         server_uri: String,
+        /// Extension / AOR user part (`sip:<username>@<domain>`).
         username: String,
         password: String,
+        /// Digest auth ID when the PBX issues one distinct from the extension.
+        auth_username: Option<String>,
+        /// SIP domain of the AOR when it differs from the server host.
+        domain: Option<String>,
+        /// Expected digest realm; challenges for other realms are not answered.
+        realm: Option<String>,
+        /// Display name sent in the From header.
+        display_name: Option<String>,
     },
     PeerToPeer {
         target_uri: String,
@@ -29,12 +57,189 @@ pub enum ConnectionMode {
     Receiver, // Just listening for incoming calls
 }
 
+/// Connection mode as picked on the registration form. `Auto` keeps the
+/// original inference from the server field for users who never pick one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ModeSelection {
+    #[default]
+    Auto,
+    Server,
+    PeerToPeer,
+    Receiver,
+}
+
+impl ModeSelection {
+    /// Resolve `Auto` from the server field: empty means Receiver, a
+    /// `user@host` address means PeerToPeer, anything else Server. Explicit
+    /// selections are returned unchanged.
+    pub fn resolve(self, server_uri: &str) -> Self {
+        match self {
+            Self::Auto if server_uri.trim().is_empty() => Self::Receiver,
+            Self::Auto if server_uri.contains('@') => Self::PeerToPeer,
+            Self::Auto => Self::Server,
+            explicit => explicit,
+        }
+    }
+
+    /// Check the form fields the (resolved) mode needs. Returns a message
+    /// suitable for the registration screen on failure.
+    pub fn validate(self, username: &str, server_uri: &str) -> Result<(), String> {
+        if username.trim().is_empty() {
+            return Err("Name is required".to_string());
+        }
+        let server_uri = server_uri.trim();
+        match self.resolve(server_uri) {
+            Self::Server if server_uri.is_empty() => {
+                Err("A SIP server is required to register".to_string())
+            }
+            Self::PeerToPeer if server_uri.is_empty() => {
+                Err("Enter the peer address to call (e.g. bob@192.168.1.20)".to_string())
+            }
+            Self::Server | Self::PeerToPeer if server_uri.contains(char::is_whitespace) => {
+                Err("Addresses cannot contain spaces".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Server => "Server",
+            Self::PeerToPeer => "P2P",
+            Self::Receiver => "Receiver",
+        }
+    }
+}
+
+/// How the client keeps the NAT binding for its registration flow open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeepaliveMode {
+    /// No keepalives; rely on the re-REGISTER interval alone.
+    Off,
+    /// RFC 5626 CRLF keepalives (double-CRLF ping on TCP/TLS, bare CRLF on UDP).
+    Crlf,
+    /// Out-of-dialog OPTIONS; unanswered pings mark the registrar unreachable.
+    Options,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeepaliveConfig {
+    pub mode: KeepaliveMode,
+    /// Time between pings. Most consumer NATs drop idle UDP bindings after 30-60s.
+    pub interval: Duration,
+    /// Consecutive unanswered OPTIONS before the registrar is reported unreachable.
+    pub max_missed: u32,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            mode: KeepaliveMode::Crlf,
+            interval: Duration::from_secs(25),
+            max_missed: 3,
+        }
+    }
+}
+
+/// Outbound proxy and route-set settings (RFC 3261 §8.1.2, RFC 3608).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyConfig {
+    /// Next hop for REGISTER (and, with `always_use_proxy`, for every initial
+    /// request), e.g. `sip:edge.carrier.net:5060`. `None` sends straight to the registrar.
+    pub outbound_proxy: Option<String>,
+    /// Preload the proxy on INVITEs too, not just on REGISTER.
+    pub always_use_proxy: bool,
+    /// Mark the proxy route `;lr`. Clear only for legacy RFC 2543 strict routers.
+    pub loose_route: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            outbound_proxy: None,
+            always_use_proxy: false,
+            loose_route: true,
+        }
+    }
+}
+
+/// Caller-ID privacy for outgoing calls (RFC 3323, RFC 3325).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivacyConfig {
+    /// Hide caller ID on every call unless a call overrides it.
+    pub hide_caller_id: bool,
+    /// Add `Privacy: id` to anonymous calls so a trusted proxy also withholds
+    /// the asserted identity from the callee.
+    pub privacy_header: bool,
+    /// P-Preferred-Identity sent on anonymous calls, telling the proxy which
+    /// of our identities to assert, e.g. `sip:+14155550100@carrier.net`.
+    pub preferred_identity: Option<String>,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            hide_caller_id: false,
+            privacy_header: true,
+            preferred_identity: None,
+        }
+    }
+}
+
+/// Static extra headers added to every request of a type.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExtraHeaders {
+    pub invite: Vec<Header>,
+    pub register: Vec<Header>,
+}
+
+/// Options chosen for a single outgoing call.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CallOptions {
+    /// Hide (`Some(true)`) or show (`Some(false)`) caller ID on this call;
+    /// `None` follows [`PrivacyConfig::hide_caller_id`].
+    pub hide_caller_id: Option<bool>,
+    /// Extra INVITE headers for this call; they replace static
+    /// [`ExtraHeaders::invite`] entries of the same name.
+    pub headers: Vec<Header>,
+}
+
+/// What to capture for pcap export (see [`crate::pcap`]). SIP is always
+/// kept in the trace buffer; RTP costs about 20 KB/s per call.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CaptureOptions {
+    /// Capture RTP as well as SIP for calls.
+    pub rtp: bool,
+    /// Write a .pcapng automatically when a call fails.
+    pub save_failed_calls: bool,
+}
+
+/// RTP packets kept per call (~15 minutes both ways at 20 ms ptime).
+const MAX_RTP_PACKETS: usize = 90_000;
+
+/// How often file playback progress is reported to the UI.
+const PLAYBACK_REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
 #[derive(Debug, Clone)]
 pub struct SipConfig {
     pub display_name: String, // User's display name
     pub connection_mode: ConnectionMode,
     pub local_port: u16,
     pub local_ip: Option<String>, // Optional local IP to bind to
+    pub keepalive: KeepaliveConfig,
+    pub proxy: ProxyConfig,
+    pub dial_plan: DialPlan,
+    pub dial_policy: DialPolicy,
+    pub privacy: PrivacyConfig,
+    pub extra_headers: ExtraHeaders,
+    pub codecs: CodecPreferences,
+    pub srtp: SrtpPolicy,
+    pub recording: RecordingPolicy,
+    /// Audio file (WAV or Ogg Opus) looped to the held party while we hold
+    /// a call; silence when unset.
+    pub music_on_hold: Option<PathBuf>,
+    pub ringing: RingConfig,
 }
 
 impl Default for SipConfig {
@@ -45,9 +250,24 @@ impl Default for SipConfig {
                 server_uri: "sip:127.0.0.1:5060".to_string(),
                 username: "user".to_string(),
                 password: "password".to_string(),
+                auth_username: None,
+                domain: None,
+                realm: None,
+                display_name: None,
             },
             local_port: 5060,
             local_ip: None,
+            keepalive: KeepaliveConfig::default(),
+            proxy: ProxyConfig::default(),
+            dial_plan: DialPlan::default(),
+            dial_policy: DialPolicy::default(),
+            privacy: PrivacyConfig::default(),
+            extra_headers: ExtraHeaders::default(),
+            codecs: CodecPreferences::default(),
+            srtp: SrtpPolicy::default(),
+            recording: RecordingPolicy::default(),
+            music_on_hold: None,
+            ringing: RingConfig::default(),
         }
     }
 }
@@ -60,6 +280,9 @@ pub enum CallState {
     Registered,
     Calling,
     Ringing,
+    /// Outgoing call not yet answered, with the network sending early
+    /// media (18x with SDP)
+    EarlyMedia,
     Connected,
     OnHold,
     Transferring,
@@ -77,6 +300,32 @@ pub struct CallInfo {
     pub is_incoming: bool,
     pub connected_at: Option<chrono::DateTime<chrono::Utc>>,
     pub is_muted: Option<bool>,
+    /// Who is on the other end, for screens and call history
+    pub identity: CallerIdentity,
+    /// Codec settled on by the SDP offer/answer, once media is up
+    pub media: Option<NegotiatedMedia>,
+    /// Latest RTCP quality snapshot
+    pub stats: Option<MediaStats>,
+    /// Set while quality has stayed below thresholds long enough to warn
+    pub quality_warning: Option<String>,
+    /// File being recorded to, while recording
+    pub recording: Option<PathBuf>,
+    /// Why the last attempt to record this call was refused
+    pub recording_refused: Option<String>,
+}
+
+/// REGISTER parameters for Server mode, derived from [`ConnectionMode::Server`].
+#[derive(Debug, Clone)]
+struct Registration {
+    /// Request-URI of the REGISTER: `sip:<domain>`.
+    registrar: String,
+    /// Server to send the REGISTER to when the domain is not itself routable
+    /// (the configured server host, when it differs from the domain).
+    next_hop: Option<String>,
+    username: String,
+    auth_username: String,
+    password: String,
+    realm: Option<String>,
 }
 
 /// SipClientManager handles SIP operations.
@@ -98,14 +347,51 @@ pub struct SipClientManager {
     pending_events: Option<EventReceiver>,
     event_sender: Option<mpsc::UnboundedSender<SipEvent>>,
     event_task: Option<tokio::task::JoinHandle<()>>,
+    /// Service-Route learned from the last successful REGISTER (RFC 3608),
+    /// preloaded on every initial request.
+    service_route: Vec<String>,
+    /// REGISTER parameters (Server mode only), kept for failover re-sends.
+    registration: Option<Registration>,
+    /// DNS used for RFC 3263 lookups; swappable for a stand-in.
+    resolver: Arc<dyn DnsResolver>,
+    /// RFC 3263 failover list for the first hop, and the one in use.
+    registrar_targets: Vec<SipTarget>,
+    active_target: usize,
+    /// NAT keepalive task, running while registered.
+    keepalive_task: Option<tokio::task::JoinHandle<()>>,
     /// Shared mute flag; the cpal bridge emits silence while set (rvoip
     /// `mute()` only signals). Shared with the active [`RunningAudio`].
     muted: Arc<AtomicBool>,
+    /// Cleared while the bridge only plays early media; the mic is neither
+    /// captured nor sent until the call is answered.
+    sending: Arc<AtomicBool>,
     /// Active cpal audio bridge for the in-progress call, if any.
     running_audio: Option<RunningAudio>,
     /// Selected capture/playback device selectors (name or index).
     audio_input_device: Option<String>,
     audio_output_device: Option<String>,
+    capture: CaptureOptions,
+    /// RTP captures of calls with media, by call id.
+    rtp_captures: HashMap<String, RtpCapture>,
+    /// Given to every audio bridge; plays clips into the call.
+    injector: AudioInjector,
+    /// Reports progress of a file played with `play_file`.
+    playback_task: Option<tokio::task::JoinHandle<()>>,
+    /// Injector id of the recording consent announcement; nothing else may
+    /// replace it until it has finished.
+    announcement: Option<u64>,
+    /// Hold state of the call with audio: music on hold plays while we hold
+    /// it (sendonly) and the other side does not hold us too (inactive).
+    held_locally: bool,
+    held_remotely: bool,
+    music_on_hold_playing: bool,
+    /// Ringtone or ringback being played, while no call audio runs.
+    tone_output: Option<OutputStream>,
+    /// Given to every audio bridge; feeds `recorder` while one runs.
+    recording_tap: RecordingTap,
+    recorder: Option<Recorder>,
+    /// Finished recordings by call id, until the call is added to history.
+    recordings: HashMap<String, Vec<PathBuf>>,
 }
 
 #[allow(dead_code)] // some accessors are retained as manager API for the UI
@@ -119,16 +405,35 @@ impl SipClientManager {
             pending_events: None,
             event_sender: None,
             event_task: None,
+            service_route: Vec::new(),
+            registration: None,
+            resolver: default_resolver(),
+            registrar_targets: Vec::new(),
+            active_target: 0,
+            keepalive_task: None,
             muted: Arc::new(AtomicBool::new(false)),
+            sending: Arc::new(AtomicBool::new(true)),
             running_audio: None,
             audio_input_device: None,
             audio_output_device: None,
+            capture: CaptureOptions::default(),
+            rtp_captures: HashMap::new(),
+            injector: AudioInjector::default(),
+            playback_task: None,
+            announcement: None,
+            held_locally: false,
+            held_remotely: false,
+            music_on_hold_playing: false,
+            tone_output: None,
+            recording_tap: RecordingTap::default(),
+            recorder: None,
+            recordings: HashMap::new(),
         }
     }
 
-    /// Build an rvoip [`Config`] for the current connection mode, plus optional
-    /// registration parameters `(registrar, username, password)`.
-    fn build_config(&self) -> Result<(Config, Option<(String, String, String)>)> {
+    /// Build an rvoip [`Config`] for the current connection mode, plus the
+    /// REGISTER parameters in Server mode.
+    fn build_config(&self) -> Result<(Config, Option<Registration>)> {
         let port = self.config.local_port;
         let bind_ip: IpAddr = self
             .config
@@ -138,21 +443,29 @@ impl SipClientManager {
             .or_else(|| local_ip_address::local_ip().ok())
             .unwrap_or(IpAddr::from([127, 0, 0, 1]));
 
-        match &self.config.connection_mode {
+        let (mut config, registration) = match &self.config.connection_mode {
             ConnectionMode::Server {
                 server_uri,
                 username,
                 password,
+                auth_username,
+                realm,
+                display_name,
+                ..
             } => {
-                let server_host = server_uri
-                    .strip_prefix("sip:")
-                    .unwrap_or(server_uri)
-                    .to_string();
-                let registrar = if server_uri.starts_with("sip:") {
-                    server_uri.clone()
-                } else {
-                    format!("sip:{}", server_uri)
-                };
+                let server = server_uri_of(server_uri)?;
+                let domain = self
+                    .sip_domain()
+                    .unwrap_or_else(|| server.host_port());
+                // The REGISTER Request-URI is the domain; when that is not the
+                // server we were given, the server becomes the next hop (with
+                // its port and transport parameters intact).
+                let registrar = SipUri::parse(&format!("sip:{}", domain))
+                    .map_err(|e| anyhow!("Invalid SIP domain '{}': {}", domain, e))?;
+                let next_hop = (registrar.host_port() != server.host_port()
+                    || !server.params.is_empty())
+                .then(|| server.to_string());
+                let registrar = registrar.to_string();
 
                 let mut config = Config::on(username, bind_ip, port);
                 // Address-of-record used in the From header (sip:user@domain).
@@ -160,18 +473,86 @@ impl SipClientManager {
                 // transport address and adopts the REGISTER credentials for
                 // challenged INVITE/BYE/REFER auth, so we no longer set
                 // config.contact_uri or config.credentials by hand.
-                config.local_uri = format!("sip:{}@{}", username, server_host);
+                let mut aor = SipUri::parse(&format!("sip:{}", domain))
+                    .map_err(|e| anyhow!("Invalid SIP domain '{}': {}", domain, e))?;
+                aor.user = Some(username.clone());
+                config.local_uri = aor.to_string();
+                config.display_name = display_name.clone();
 
-                Ok((
+                let auth_username = auth_username
+                    .clone()
+                    .unwrap_or_else(|| username.clone());
+                (
                     config,
-                    Some((registrar, username.clone(), password.clone())),
-                ))
+                    Some(Registration {
+                        registrar,
+                        next_hop,
+                        username: username.clone(),
+                        auth_username,
+                        password: password.clone(),
+                        realm: realm.clone(),
+                    }),
+                )
             }
             ConnectionMode::PeerToPeer { .. } | ConnectionMode::Receiver => {
                 // No registration; identity is sip:display_name@ip:port.
-                let config = Config::on(&self.config.display_name, bind_ip, port);
-                Ok((config, None))
+                (Config::on(&self.config.display_name, bind_ip, port), None)
             }
+        };
+
+        self.apply_codecs(&mut config);
+        self.apply_srtp(&mut config);
+        // Raw SIP for the trace viewer (Event::MessageTrace)
+        config.trace_messages = true;
+        Ok((config, registration))
+    }
+
+    /// Offer the enabled codecs in preference order. rvoip builds the SDP
+    /// `m=` line from `preferred_codecs` and answers with the first entry of
+    /// ours the remote also lists.
+    fn apply_codecs(&self, config: &mut Config) {
+        let codecs = &self.config.codecs;
+        config.preferred_codecs = codecs
+            .enabled()
+            .into_iter()
+            .map(|c| c.sdp_name().to_string())
+            .collect();
+        config.opus_fmtp = codecs
+            .enabled()
+            .contains(&Codec::Opus)
+            .then(|| codecs.opus.fmtp());
+        config.ptime_ms = Some(codecs.ptime_ms);
+        info!(
+            "Codec preference: {} (ptime {} ms)",
+            config.preferred_codecs.join(", "),
+            codecs.ptime_ms
+        );
+    }
+
+    /// SDES-SRTP: `Offered` sends RTP/AVP with `a=crypto` lines so plain
+    /// peers can still answer; `Required` offers RTP/SAVP and makes rvoip
+    /// refuse answers without keys.
+    fn apply_srtp(&self, config: &mut Config) {
+        let policy = self.config.srtp;
+        config.srtp = match policy {
+            SrtpPolicy::Off => SrtpMode::Disabled,
+            SrtpPolicy::Optional => SrtpMode::Offered,
+            SrtpPolicy::Mandatory => SrtpMode::Required,
+        };
+        config.srtp_suites = policy.suites().iter().map(|s| s.to_string()).collect();
+        info!("Media encryption: {}", policy.label());
+    }
+
+    /// Domain part of our AOR in Server mode: the configured SIP domain, or
+    /// the server host when none is set.
+    pub fn sip_domain(&self) -> Option<String> {
+        match &self.config.connection_mode {
+            ConnectionMode::Server {
+                server_uri, domain, ..
+            } => server_uri_of(domain.as_deref().unwrap_or(server_uri))
+                .ok()
+                .map(|uri| uri.host_port()),
+            _ => None,
         }
     }
 
@@ -183,6 +564,7 @@ impl SipClientManager {
         if let Some(task) = self.event_task.take() {
             task.abort();
         }
+        self.stop_keepalive();
         self.stop_audio();
         self.reg_handle = None;
         self.control = None;
@@ -191,7 +573,10 @@ impl SipClientManager {
             let _ = coord.shutdown_gracefully(Some(Duration::from_secs(1))).await;
         }
 
-        let registration = self.build_config()?.1;
+        self.registration = self.build_config()?.1;
+        self.service_route.clear();
+        self.registrar_targets = self.resolve_first_hop().await;
+        self.active_target = 0;
 
         // rvoip-sip now sets SO_REUSEADDR on the UDP bind, so a re-login can
         // rebind the same port without racing the previous socket's release.
@@ -212,34 +597,12 @@ impl SipClientManager {
         let (control, events) = peer.split();
         self.coordinator = Some(control.coordinator().clone());
 
-        // Server mode registers immediately; success/failure arrives as an event.
-        if let Some((registrar, username, password)) = registration {
-            // rvoip-sip now defaults the Contact to the bound transport address,
-            // so we no longer pass an explicit contact here.
-            let builder = control
-                .register(registrar.clone(), username, password)
-                .with_expires(3600);
-            match builder.send().await {
-                Ok(handle) => {
-                    info!("REGISTER sent to {}", registrar);
-                    self.reg_handle = Some(handle);
-                }
-                Err(e) => {
-                    // Non-fatal here: surface via the error channel; the user can retry.
-                    error!("Registration request failed: {}", e);
-                    if let Some(sender) = &self.event_sender {
-                        let _ = sender.send(SipEvent::RegistrationFailed {
-                            registrar,
-                            reason: e.to_string(),
-                        });
-                    }
-                }
-            }
-        }
-
         self.control = Some(control);
         self.pending_events = Some(events);
 
+        // Server mode registers immediately; success/failure arrives as an event.
+        self.send_register().await;
+
         info!(
             "SIP client initialized in {} mode",
             match &self.config.connection_mode {
@@ -251,6 +614,117 @@ impl SipClientManager {
         Ok(())
     }
 
+    /// Send the Server-mode REGISTER through the current next hop (no-op in
+    /// other modes). Replaces any previous registration.
+    async fn send_register(&mut self) {
+        let (Some(control), Some(reg)) = (self.control.clone(), self.registration.clone()) else {
+            return;
+        };
+        self.reg_handle = None;
+
+        let registrar = reg.registrar.clone();
+        let auth_username = (reg.auth_username != reg.username).then_some(reg.auth_username);
+        // rvoip-sip now defaults the Contact to the bound transport address,
+        // so we no longer pass an explicit contact here.
+        let mut builder = control
+            .register(registrar.clone(), reg.username, reg.password)
+            .with_expires(3600);
+        if let Some(auth_username) = auth_username {
+            builder = builder.with_auth_username(auth_username);
+        }
+        if let Some(realm) = reg.realm {
+            builder = builder.with_realm(realm);
+        }
+        if let Some(hop) = self.next_hop() {
+            info!("REGISTER {} via {}", registrar, hop);
+            builder = builder.with_outbound_proxy(hop);
+        }
+        for (name, value) in &self.config.extra_headers.register {
+            builder = builder.with_header(name.clone(), value.clone());
+        }
+        match builder.send().await {
+            Ok(handle) => {
+                info!("REGISTER sent to {}", registrar);
+                self.reg_handle = Some(handle);
+            }
+            Err(e) => {
+                // Non-fatal here: surface via the error channel; the user can retry.
+                error!("Registration request failed: {}", e);
+                if let Some(sender) = &self.event_sender {
+                    let _ = sender.send(SipEvent::RegistrationFailed {
+                        registrar,
+                        code: None,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Resolve the first hop (outbound proxy, else server) into an RFC 3263
+    /// failover list. Failure is not fatal: rvoip then does its own A/AAAA
+    /// lookup of the configured host.
+    async fn resolve_first_hop(&self) -> Vec<SipTarget> {
+        let Some(reg) = &self.registration else {
+            return Vec::new();
+        };
+        let hop = self
+            .proxy_route()
+            .or_else(|| reg.next_hop.clone())
+            .unwrap_or_else(|| reg.registrar.clone());
+        let uri = match SipUri::parse(&hop) {
+            Ok(uri) => uri,
+            Err(e) => {
+                warn!("Cannot resolve first hop '{}': {}", hop, e);
+                return Vec::new();
+            }
+        };
+        let transport = if uri.scheme == Scheme::Sips {
+            Some(Transport::Tls)
+        } else {
+            uri.param("transport")
+                .flatten()
+                .and_then(Transport::from_param)
+        };
+        match sip_resolver::resolve(self.resolver.as_ref(), &uri.host, uri.port, transport).await {
+            Ok(targets) => targets,
+            Err(e) => {
+                warn!("RFC 3263 resolution of {} failed: {}", uri.host, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// First hop for REGISTER: the active RFC 3263 target when DNS produced
+    /// one, else the outbound proxy or distinct server as configured.
+    fn next_hop(&self) -> Option<String> {
+        if let Some(target) = self.registrar_targets.get(self.active_target) {
+            return Some(target.route_uri(self.config.proxy.loose_route));
+        }
+        self.proxy_route()
+            .or_else(|| self.registration.as_ref()?.next_hop.clone())
+    }
+
+    /// Re-send REGISTER to the next RFC 3263 target after a timeout or 503.
+    /// Returns `false` when every target has been tried.
+    pub async fn fail_over_registration(&mut self) -> bool {
+        if self.active_target + 1 >= self.registrar_targets.len() {
+            return false;
+        }
+        self.active_target += 1;
+        warn!(
+            "Registrar failover to target {}/{}: {}",
+            self.active_target + 1,
+            self.registrar_targets.len(),
+            self.registrar_targets[self.active_target]
+        );
+        self.send_register().await;
+        if self.keepalive_task.is_some() {
+            self.start_keepalive();
+        }
+        true
+    }
+
     pub fn set_event_sender(&mut self, sender: mpsc::UnboundedSender<SipEvent>) {
         self.event_sender = Some(sender);
     }
@@ -281,25 +755,64 @@ impl SipClientManager {
 
         self.event_task = Some(task);
         info!("Event loop started");
+        self.start_keepalive();
         Ok(())
     }
 
+    /// Start pinging the registrar per [`KeepaliveConfig`] (Server mode only).
+    fn start_keepalive(&mut self) {
+        self.stop_keepalive();
+        if self.config.keepalive.mode == KeepaliveMode::Off {
+            return;
+        }
+        // Keepalives must travel the same flow as REGISTER to hold its NAT binding open.
+        let Some(control) = self.control.clone() else {
+            return;
+        };
+        let Some(registrar) = self
+            .registration
+            .as_ref()
+            .map(|reg| self.next_hop().unwrap_or_else(|| reg.registrar.clone()))
+        else {
+            return;
+        };
+        info!(
+            "Starting {:?} keepalives to {} every {:?}",
+            self.config.keepalive.mode, registrar, self.config.keepalive.interval
+        );
+        self.keepalive_task = Some(tokio::spawn(run_keepalive(
+            control,
+            registrar,
+            self.config.keepalive.clone(),
+            self.event_sender.clone(),
+        )));
+    }
+
+    fn stop_keepalive(&mut self) {
+        if let Some(task) = self.keepalive_task.take() {
+            task.abort();
+        }
+    }
+
     pub async fn register(&mut self) -> Result<()> {
         // Registration happens during initialize() for Server mode.
         info!("register(): registration is performed during initialize()");
         Ok(())
     }
 
-    pub async fn make_call(&mut self, target_uri: &str) -> Result<String> {
-        let formatted_uri = self.format_target_uri(target_uri);
+    /// Place a call to `target_uri`. Targets refused by the dialing policy
+    /// fail with [`SipError::InvalidParameters`] unless `override_pin`
+    /// matches the policy's PIN.
+    pub async fn make_call(
+        &mut self,
+        target_uri: &str,
+        options: &CallOptions,
+        override_pin: Option<&str>,
+    ) -> Result<String> {
+        let formatted_uri = self.authorized_target(target_uri, override_pin)?;
         info!("Making call to {} (formatted: {})", target_uri, formatted_uri);
 
-        let control = self
-            .control
-            .as_ref()
-            .ok_or_else(|| anyhow!("Client not initialized"))?;
-
-        match control.invite(formatted_uri).send().await {
+        match self.send_invite(formatted_uri, options).await {
             Ok(call_id) => {
                 self.muted.store(false, Ordering::SeqCst);
                 let id = call_id.to_string();
@@ -308,47 +821,158 @@ impl SipClientManager {
             }
             Err(e) => {
                 error!("Make call failed: {}", e);
-                Err(e.into())
+                Err(e)
             }
         }
     }
 
-    /// Format a dialed target into a SIP URI based on the connection mode.
-    fn format_target_uri(&self, target_uri: &str) -> String {
-        match &self.config.connection_mode {
+    /// Send an INVITE to an already-formatted `uri`, preloading the route set.
+    async fn send_invite(&self, uri: String, options: &CallOptions) -> Result<CallId> {
+        let control = self
+            .control
+            .as_ref()
+            .ok_or_else(|| anyhow!("Client not initialized"))?;
+        let mut invite = control.invite(uri);
+        let routes = self.route_set();
+        if !routes.is_empty() {
+            info!("INVITE route set: {:?}", routes);
+            invite = invite.with_route_set(routes);
+        }
+
+        let privacy = &self.config.privacy;
+        let mut headers = Vec::new();
+        if options.hide_caller_id.unwrap_or(privacy.hide_caller_id) {
+            // RFC 3323 §4.1.1 anonymous From; the real identity, if any, only
+            // travels to the proxy in P-Preferred-Identity.
+            info!("Withholding caller ID on this call");
+            invite = invite.with_from(caller_id::ANONYMOUS_FROM.to_string());
+            if privacy.privacy_header {
+                headers.push(("Privacy".to_string(), "id".to_string()));
+            }
+            if let Some(identity) = &privacy.preferred_identity {
+                headers.push(("P-Preferred-Identity".to_string(), format!("<{}>", identity)));
+            }
+        }
+        // Static profile headers, then per-call ones, each replacing same-named
+        // headers from the previous layer.
+        let headers = sip_headers::merge(
+            &sip_headers::merge(&headers, &self.config.extra_headers.invite),
+            &options.headers,
+        );
+        for (name, value) in headers {
+            debug!("INVITE header {}: {}", name, value);
+            invite = invite.with_header(name, value);
+        }
+        Ok(invite.send().await?)
+    }
+
+    /// Record the Service-Route returned in the REGISTER 200 OK (RFC 3608).
+    pub fn set_service_route(&mut self, routes: Vec<String>) {
+        if !routes.is_empty() {
+            info!("Service-Route: {:?}", routes);
+        }
+        self.service_route = routes;
+    }
+
+    /// Route set preloaded on initial requests: the outbound proxy (when it
+    /// applies to all requests) followed by the registrar's Service-Route.
+    /// Without either, calls follow REGISTER to the DNS-resolved (or
+    /// distinct) server so the domain need not be routable; with a
+    /// REGISTER-only proxy they go to the registrar's server instead.
+    fn route_set(&self) -> Vec<String> {
+        let mut routes = Vec::new();
+        let has_proxy = self.proxy_route().is_some();
+        if has_proxy && self.config.proxy.always_use_proxy {
+            routes.extend(self.next_hop());
+        } else if self.registration.is_some() && self.service_route.is_empty() {
+            // Without a proxy, next_hop() is the registrar's own (resolved) hop
+            routes.extend(if has_proxy { self.registrar_route() } else { self.next_hop() });
+        }
+        routes.extend(self.service_route.iter().cloned());
+        routes
+    }
+
+    /// The registrar's server as a route URI, bypassing the outbound proxy:
+    /// the distinct server when there is one, else the registrar itself.
+    fn registrar_route(&self) -> Option<String> {
+        let reg = self.registration.as_ref()?;
+        let hop = reg.next_hop.as_deref().unwrap_or(&reg.registrar);
+        let mut uri = SipUri::parse(hop).ok()?;
+        if self.config.proxy.loose_route {
+            uri.set_param("lr", None);
+        } else {
+            uri.remove_param("lr");
+        }
+        Some(uri.to_string())
+    }
+
+    /// The configured outbound proxy as a route URI, with `;lr` added or
+    /// stripped per [`ProxyConfig::loose_route`].
+    fn proxy_route(&self) -> Option<String> {
+        let proxy = self.config.proxy.outbound_proxy.as_deref()?.trim();
+        if proxy.is_empty() {
+            return None;
+        }
+        let mut uri = match sip_uri::dial_target(proxy, None) {
+            Ok(uri) => uri,
+            Err(e) => {
+                warn!("Ignoring invalid outbound proxy '{}': {}", proxy, e);
+                return None;
+            }
+        };
+        if self.config.proxy.loose_route {
+            uri.set_param("lr", None);
+        } else {
+            uri.remove_param("lr");
+        }
+        Some(uri.to_string())
+    }
+
+    /// Format a dialed or transfer target into a request URI based on the
+    /// connection mode and dial plan. Bare extensions go to our SIP domain
+    /// (Server) or the connected peer's host (P2P); phone numbers are
+    /// normalized per [`DialPlan`]; full URIs keep their port, `sips:` and
+    /// parameters.
+    fn format_target_uri(&self, target_uri: &str) -> Result<SipUri> {
+        let default_host = match &self.config.connection_mode {
             ConnectionMode::PeerToPeer {
                 target_uri: connected_peer,
-            } => {
-                if target_uri.contains('@') || target_uri.starts_with("sip:") {
-                    target_uri.to_string()
-                } else if let Some(at_pos) = connected_peer.find('@') {
-                    let domain = &connected_peer[at_pos + 1..];
-                    format!("sip:{}@{}", target_uri, domain)
-                } else {
-                    format!("sip:{}", target_uri)
+            } => sip_uri::dial_target(connected_peer, None)
+                .ok()
+                .map(|peer| peer.host_port()),
+            // Dial extensions within our SIP domain: sip:<ext>@<domain>,
+            // so the INVITE targets the server (which routes by dialplan)
+            // rather than trying to DNS-resolve a bare extension.
+            ConnectionMode::Server { .. } => self.sip_domain(),
+            ConnectionMode::Receiver => None,
+        };
+        self.config
+            .dial_plan
+            .target(target_uri, default_host.as_deref())
+            .map_err(|e| anyhow!("Invalid SIP address '{}': {}", target_uri.trim(), e))
+    }
+
+    /// Format `target_uri` and check it against the dialing policy, which
+    /// guards every outgoing INVITE and REFER we originate.
+    fn authorized_target(&self, target_uri: &str, override_pin: Option<&str>) -> Result<String> {
+        let uri = self.format_target_uri(target_uri)?;
+        let policy = &self.config.dial_policy;
+        if let Err(violation) = policy.check(&uri, &self.config.dial_plan) {
+            match override_pin {
+                Some(pin) if policy.accepts_pin(pin) => {
+                    warn!("Dialing policy overridden by PIN for {}", violation.number);
                 }
-            }
-            ConnectionMode::Server { server_uri, .. } => {
-                // Dial extensions through the registrar: sip:<ext>@<server-host>,
-                // so the INVITE targets the server (which routes by dialplan)
-                // rather than trying to DNS-resolve a bare extension.
-                if target_uri.starts_with("sip:") {
-                    target_uri.to_string()
-                } else if target_uri.contains('@') {
-                    format!("sip:{}", target_uri)
-                } else {
-                    let server_host = server_uri.strip_prefix("sip:").unwrap_or(server_uri);
-                    format!("sip:{}@{}", target_uri, server_host)
+                Some(_) => {
+                    warn!("Wrong override PIN for {}", violation.number);
+                    return Err(SipError::InvalidParameters("Incorrect override PIN".to_string()).into());
                 }
-            }
-            ConnectionMode::Receiver => {
-                if target_uri.starts_with("sip:") {
-                    target_uri.to_string()
-                } else {
-                    format!("sip:{}", target_uri)
+                None => {
+                    warn!("Refusing call: {}", violation.reason);
+                    return Err(SipError::InvalidParameters(violation.reason).into());
                 }
             }
         }
+        Ok(uri.to_string())
     }
 
     pub async fn hangup(&mut self, call_id_str: &str) -> Result<()> {
@@ -391,14 +1015,24 @@ impl SipClientManager {
 
     /// Reject an incoming (ringing) call with 486 Busy Here.
     pub async fn reject_call(&mut self, call_id_str: &str) -> Result<()> {
-        info!("Rejecting incoming call: {}", call_id_str);
+        self.reject_with(call_id_str, 486, "Busy Here").await
+    }
+
+    /// Reject an incoming call whose offer has no SRTP keys while the
+    /// encryption policy is mandatory (488, RFC 3261 §21.4.26).
+    pub async fn reject_unencrypted_call(&mut self, call_id_str: &str) -> Result<()> {
+        self.reject_with(call_id_str, 488, "Not Acceptable Here").await
+    }
+
+    async fn reject_with(&self, call_id_str: &str, status: u16, reason: &str) -> Result<()> {
+        info!("Rejecting incoming call {} with {} {}", call_id_str, status, reason);
         let control = self
             .control
             .as_ref()
             .ok_or_else(|| anyhow!("Client not initialized"))?;
         let id = CallId::from_string(call_id_str);
         control
-            .reject(&id, 486, "Busy Here")
+            .reject(&id, status, reason)
             .await
             .map_err(|e| anyhow!("Reject failed: {}", e))
     }
@@ -416,10 +1050,10 @@ impl SipClientManager {
                         .map(|ip| ip.to_string())
                         .unwrap_or_else(|_| "127.0.0.1".to_string())
                 });
-                Some(format!(
-                    "{}@{}:{}",
-                    self.config.display_name, local_ip, self.config.local_port
-                ))
+                let mut uri = SipUri::sip(local_ip);
+                uri.user = Some(self.config.display_name.clone());
+                uri.port = Some(self.config.local_port);
+                Some(uri.without_scheme())
             }
             _ => None,
         }
@@ -458,25 +1092,74 @@ impl SipClientManager {
     }
 
     /// Put the active call on hold.
-    pub async fn hold(&self, call_id_str: &str) -> Result<()> {
+    pub async fn hold(&mut self, call_id_str: &str) -> Result<()> {
         info!("hold for call: {}", call_id_str);
         let coord = self.coord()?;
         let id = CallId::from_string(call_id_str);
+        // rvoip offers a=sendonly, so the held party can still hear us
         coord.session(&id).hold().await?;
         info!("Call put on hold");
+        self.held_locally = true;
+        self.update_music_on_hold();
         Ok(())
     }
 
     /// Resume a held call.
-    pub async fn resume(&self, call_id_str: &str) -> Result<()> {
+    pub async fn resume(&mut self, call_id_str: &str) -> Result<()> {
         info!("resume for call: {}", call_id_str);
         let coord = self.coord()?;
         let id = CallId::from_string(call_id_str);
         coord.session(&id).resume().await?;
         info!("Call resumed");
+        self.held_locally = false;
+        self.update_music_on_hold();
         Ok(())
     }
 
+    /// The other party put us on hold (or resumed). While they hold us too,
+    /// media is inactive and hold music would go nowhere.
+    pub fn set_remote_hold(&mut self, held: bool) {
+        self.held_remotely = held;
+        self.update_music_on_hold();
+    }
+
+    /// Start or stop the hold music to match the hold state. Also called
+    /// when a playback ends, since hold music waits for the recording
+    /// announcement rather than cutting it off.
+    pub fn update_music_on_hold(&mut self) {
+        let rate = self.bridge_rate();
+        let wanted = self.held_locally && !self.held_remotely && rate.is_some();
+        if wanted && !self.music_on_hold_playing {
+            let (Some(path), Some(rate)) = (self.config.music_on_hold.clone(), rate) else {
+                return;
+            };
+            if self.announcement_playing() {
+                info!("Music on hold waits for the recording announcement to finish");
+                return;
+            }
+            match AudioClip::load(&path) {
+                Ok(clip) => {
+                    info!("Playing music on hold {}", path.display());
+                    self.injector.play(
+                        clip,
+                        PlaybackOptions {
+                            mode: PlaybackMode::Replace,
+                            local: false,
+                            looped: true,
+                        },
+                        rate,
+                    );
+                    self.music_on_hold_playing = true;
+                }
+                Err(e) => warn!("Music on hold {} cannot be played: {}", path.display(), e),
+            }
+        } else if !wanted && self.music_on_hold_playing {
+            info!("Stopping music on hold");
+            self.injector.stop();
+            self.music_on_hold_playing = false;
+        }
+    }
+
     /// Send a DTMF digit on the active call.
     pub async fn send_dtmf(&self, call_id_str: &str, digit: char) -> Result<()> {
         let coord = self.coord()?;
@@ -486,11 +1169,17 @@ impl SipClientManager {
     }
 
     /// Blind-transfer the active call to `target_uri` (RFC 3515).
-    pub async fn transfer(&self, call_id_str: &str, target_uri: &str) -> Result<()> {
-        info!("Blind transfer {} -> {}", call_id_str, target_uri);
+    pub async fn transfer(
+        &self,
+        call_id_str: &str,
+        target_uri: &str,
+        override_pin: Option<&str>,
+    ) -> Result<()> {
+        let target = self.authorized_target(target_uri, override_pin)?;
+        info!("Blind transfer {} -> {}", call_id_str, target);
         let coord = self.coord()?;
         let id = CallId::from_string(call_id_str);
-        coord.session(&id).transfer_blind(target_uri).await?;
+        coord.session(&id).transfer_blind(&target).await?;
         Ok(())
     }
 
@@ -499,40 +1188,39 @@ impl SipClientManager {
     /// `05-blind-transfer`. Returns the new call id.
     pub async fn follow_refer(&mut self, original_id: &str, refer_to: &str) -> Result<String> {
         info!("Following REFER {} -> {}", original_id, refer_to);
+        // Remote parties cannot steer us around the dialing policy.
+        let target = self.authorized_target(refer_to, None)?;
         let coord = self
             .coordinator
             .clone()
             .ok_or_else(|| anyhow!("Client not initialized"))?;
-        let control = self
-            .control
-            .clone()
-            .ok_or_else(|| anyhow!("Client not initialized"))?;
         let oid = CallId::from_string(original_id);
         let _ = coord.session(&oid).hangup().await;
         self.stop_audio();
-        let new_id = control.invite(refer_to.to_string()).send().await?;
+        let new_id = self.send_invite(target, &CallOptions::default()).await?;
         Ok(new_id.to_string())
     }
 
     /// Begin an attended transfer: hold + detach audio from the original call
     /// and place a consultation call to `target`. Returns the consultation call
     /// id (the caller should start audio for it once it answers).
-    pub async fn start_consultation(&mut self, original_id: &str, target: &str) -> Result<String> {
+    pub async fn start_consultation(
+        &mut self,
+        original_id: &str,
+        target: &str,
+        override_pin: Option<&str>,
+    ) -> Result<String> {
         info!("Attended transfer: consulting {} (original {})", target, original_id);
+        let formatted = self.authorized_target(target, override_pin)?;
         let coord = self
             .coordinator
             .clone()
             .ok_or_else(|| anyhow!("Client not initialized"))?;
-        let control = self
-            .control
-            .clone()
-            .ok_or_else(|| anyhow!("Client not initialized"))?;
         let oid = CallId::from_string(original_id);
         // Hold the original and free the mic/speaker for the consultation leg.
         let _ = coord.session(&oid).hold().await;
         self.stop_audio();
-        let formatted = self.format_target_uri(target);
-        let consult_id = control.invite(formatted).send().await?;
+        let consult_id = self.send_invite(formatted, &CallOptions::default()).await?;
         Ok(consult_id.to_string())
     }
 
@@ -560,7 +1248,7 @@ impl SipClientManager {
             .await?
             .and_then(|id| id.to_replaces_value())
             .ok_or_else(|| anyhow!("consultation dialog not yet confirmed"))?;
-        let refer_to = self.format_target_uri(target);
+        let refer_to = self.format_target_uri(target)?.to_string();
         let original = CallId::from_string(original_id);
         coord
             .session(&original)
@@ -593,8 +1281,68 @@ impl SipClientManager {
         Ok(())
     }
 
-    /// Start the cpal audio bridge for `call_id_str` (idempotent).
+    /// Ring for an incoming call on the configured ring device, unless
+    /// another call's audio is running.
+    pub fn start_ringtone(&mut self) {
+        if self.running_audio.is_none() {
+            let device = self.config.ringing.device.clone();
+            match ToneSource::ringtone_clip(&self.config.ringing) {
+                Some(clip) => {
+                    self.stop_tones();
+                    match ToneOutput::start_file(device, clip) {
+                        Ok(output) => self.tone_output = Some(output),
+                        Err(e) => warn!("Cannot play ringing tone: {}", e),
+                    }
+                }
+                None => self.start_tone(device, ToneSource::ringtone()),
+            }
+        }
+    }
+
+    /// Play the ringback tone while an outgoing call rings without early
+    /// media.
+    pub fn start_ringback(&mut self) {
+        if self.running_audio.is_none() && self.tone_output.is_none() {
+            let source = ToneSource::ringback(self.config.ringing.ringback);
+            self.start_tone(self.audio_output_device.clone(), source);
+        }
+    }
+
+    fn start_tone(&mut self, device: Option<String>, source: ToneSource) {
+        self.stop_tones();
+        match ToneOutput::start(device, source) {
+            Ok(output) => self.tone_output = Some(output),
+            Err(e) => warn!("Cannot play ringing tone: {}", e),
+        }
+    }
+
+    /// Silence the ringtone or ringback, if playing.
+    pub fn stop_tones(&mut self) {
+        self.tone_output = None;
+    }
+
+    /// Start the cpal audio bridge for `call_id_str` (idempotent). A bridge
+    /// already playing early media starts sending the mic too.
     pub async fn start_audio(&mut self, call_id_str: &str) -> Result<()> {
+        self.sending.store(true, Ordering::SeqCst);
+        self.start_bridge(call_id_str).await
+    }
+
+    /// Start the bridge receive-only, to play the early media (183 Session
+    /// Progress with SDP) of an outgoing call. The mic joins in on answer,
+    /// through [`start_audio`](Self::start_audio).
+    pub async fn start_early_media(&mut self, call_id_str: &str) -> Result<()> {
+        if self.running_audio.is_some() {
+            return Ok(());
+        }
+        self.sending.store(false, Ordering::SeqCst);
+        info!("Playing early media for call {}", call_id_str);
+        self.start_bridge(call_id_str).await
+    }
+
+    async fn start_bridge(&mut self, call_id_str: &str) -> Result<()> {
+        // The network's audio replaces our ringback
+        self.stop_tones();
         if self.running_audio.is_some() {
             return Ok(());
         }
@@ -604,21 +1352,217 @@ impl SipClientManager {
             .ok_or_else(|| anyhow!("Client not initialized"))?;
         let id = CallId::from_string(call_id_str);
         let audio = coord.session(&id).audio().await?;
+        if self.capture.rtp && !self.rtp_captures.contains_key(call_id_str) {
+            match coord.session(&id).capture_rtp(MAX_RTP_PACKETS).await {
+                Ok(capture) => {
+                    self.rtp_captures.insert(call_id_str.to_string(), capture);
+                }
+                Err(e) => warn!("RTP capture unavailable for call {}: {}", call_id_str, e),
+            }
+        }
         self.muted.store(false, Ordering::SeqCst);
         let running = AudioBridge::start(
             audio,
             self.audio_input_device.clone(),
             self.audio_output_device.clone(),
-            self.muted.clone(),
-            self.event_sender.clone(),
+            BridgeHooks {
+                muted: self.muted.clone(),
+                sending: self.sending.clone(),
+                event_tx: self.event_sender.clone(),
+                frames: Arc::new(CallFrames {
+                    injector: self.injector.clone(),
+                    recording: self.recording_tap.clone(),
+                }),
+            },
         )?;
         self.running_audio = Some(running);
         info!("Audio bridge started for call {}", call_id_str);
         Ok(())
     }
 
-    /// Stop the cpal audio bridge, if running.
+    pub fn capture_options(&self) -> &CaptureOptions {
+        &self.capture
+    }
+
+    pub fn set_capture_options(&mut self, options: CaptureOptions) {
+        info!("Capture options: {:?}", options);
+        self.capture = options;
+    }
+
+    /// RTP captured so far for `call_id`, as packets for pcap export.
+    pub fn rtp_packets(&self, call_id: &str) -> Vec<CapturedPacket> {
+        let Some(capture) = self.rtp_captures.get(call_id) else {
+            return Vec::new();
+        };
+        capture
+            .packets()
+            .into_iter()
+            .map(|packet| {
+                let (src, dst) = if packet.outbound {
+                    (packet.local_addr, packet.remote_addr)
+                } else {
+                    (packet.remote_addr, packet.local_addr)
+                };
+                CapturedPacket {
+                    at: packet.at.into(),
+                    src,
+                    dst,
+                    payload: packet.data.to_vec(),
+                    comment: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Drop the RTP capture of a call that has ended.
+    pub fn discard_capture(&mut self, call_id: &str) {
+        self.rtp_captures.remove(call_id);
+    }
+
+    /// Sample rate of the running audio bridge, which played clips are
+    /// resampled to.
+    fn bridge_rate(&self) -> Option<u32> {
+        self.running_audio.as_ref().map(|audio| audio.sample_rate())
+    }
+
+    /// Current depth of the audio bridge's playout jitter buffer, in ms.
+    pub fn jitter_buffer_ms(&self) -> Option<u32> {
+        self.running_audio
+            .as_ref()
+            .map(|audio| audio.jitter_buffer_depth().as_millis() as u32)
+    }
+
+    /// Start recording `call` to a new file as the recording policy says.
+    /// Audio flows in whenever the bridge is running.
+    ///
+    /// With an announcement configured, it is played to the call first and
+    /// recorded along with it; if it cannot be played, nothing is recorded.
+    pub fn start_recording(&mut self, call: &CallInfo) -> Result<PathBuf> {
+        if let Some(recorder) = &self.recorder {
+            return Err(anyhow!("Already recording call {}", recorder.call_id()));
+        }
+        if let Some(announcement) = &self.config.recording.announcement {
+            let Some(rate) = self.bridge_rate() else {
+                return Err(anyhow!("Recording announcement cannot be played: call audio is not running"));
+            };
+            let clip = AudioClip::load(announcement).map_err(|e| {
+                anyhow!("Recording announcement {} cannot be played: {}", announcement.display(), e)
+            })?;
+            if clip.samples.is_empty() {
+                return Err(anyhow!("Recording announcement {} is empty", announcement.display()));
+            }
+            info!("Playing recording announcement {} ({:.1}s)", announcement.display(), clip.duration().as_secs_f32());
+            self.music_on_hold_playing = false;
+            let id = self.injector.play(
+                clip,
+                PlaybackOptions {
+                    mode: PlaybackMode::Replace,
+                    local: !self.config.recording.announce_remote_only,
+                    looped: false,
+                },
+                rate,
+            );
+            self.announcement = Some(id);
+            if let Some(tx) = self.event_sender.clone() {
+                tokio::spawn(await_announcement(self.injector.clone(), id, tx));
+            }
+        }
+        let metadata = RecordingMetadata {
+            call_id: call.id.clone(),
+            remote: call.identity.label(),
+            remote_uri: call.identity.uri.clone().filter(|_| !call.identity.anonymous),
+            is_incoming: call.is_incoming,
+            account: logging::account(),
+            started: chrono::Local::now(),
+        };
+        let recorder = Recorder::start(&self.recording_tap, &self.config.recording, metadata).inspect_err(|_| {
+            if self.config.recording.announcement.is_some() {
+                self.injector.stop();
+            }
+        })?;
+        let path = recorder.path().to_path_buf();
+        self.recorder = Some(recorder);
+        Ok(path)
+    }
+
+    /// Finish the running recording, if any. The file is kept for
+    /// [`take_recordings`](Self::take_recordings).
+    pub fn stop_recording(&mut self) -> Option<PathBuf> {
+        let recorder = self.recorder.take()?;
+        let call_id = recorder.call_id().to_string();
+        match recorder.finish() {
+            Ok(path) => {
+                self.recordings.entry(call_id).or_default().push(path.clone());
+                Some(path)
+            }
+            Err(e) => {
+                error!("Failed to finish recording of call {}: {}", call_id, e);
+                None
+            }
+        }
+    }
+
+    /// Files recorded during `call_id`, handed over once the call is over.
+    pub fn take_recordings(&mut self, call_id: &str) -> Vec<PathBuf> {
+        self.recordings.remove(call_id).unwrap_or_default()
+    }
+
+    /// Play an audio file (WAV or Ogg Opus) into the running call, instead
+    /// of or on top of the microphone; we hear it too. Progress arrives as
+    /// [`SipEvent::Playback`] until it ends or is stopped.
+    pub fn play_file(&mut self, path: &std::path::Path, mode: PlaybackMode) -> Result<()> {
+        let Some(rate) = self.bridge_rate() else {
+            return Err(anyhow!("No call audio to play into"));
+        };
+        if self.announcement_playing() {
+            return Err(anyhow!("Wait for the recording announcement to finish"));
+        }
+        let clip = AudioClip::load(path).map_err(|e| anyhow!("Cannot play {}: {}", path.display(), e))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        info!("Playing {} into the call ({:?}, {:.1}s)", name, mode, clip.duration().as_secs_f32());
+        let id = self.injector.play(clip, PlaybackOptions { mode, local: true, looped: false }, rate);
+        // Whatever was playing (hold music included) has been replaced
+        self.music_on_hold_playing = false;
+        if let Some(task) = self.playback_task.take() {
+            task.abort();
+        }
+        if let Some(tx) = self.event_sender.clone() {
+            self.playback_task = Some(tokio::spawn(report_playback(self.injector.clone(), id, name, tx)));
+        }
+        Ok(())
+    }
+
+    /// Stop whatever is being played into the call, except the recording
+    /// announcement.
+    pub fn stop_playback(&mut self) {
+        if self.announcement_playing() {
+            return;
+        }
+        self.injector.stop();
+        self.music_on_hold_playing = false;
+    }
+
+    fn announcement_playing(&self) -> bool {
+        self.announcement.is_some_and(|id| {
+            self.injector
+                .progress()
+                .is_some_and(|progress| progress.id == id && !progress.finished)
+        })
+    }
+
+    /// Stop the cpal audio bridge, if running, and any recording of it or
+    /// ringing tone.
     pub fn stop_audio(&mut self) {
+        self.stop_tones();
+        self.stop_recording();
+        self.injector.stop();
+        self.announcement = None;
+        self.held_locally = false;
+        self.held_remotely = false;
+        self.music_on_hold_playing = false;
         if self.running_audio.take().is_some() {
             info!("Audio bridge stopped");
         }
@@ -659,6 +1603,120 @@ impl Drop for SipClientManager {
         if let Some(task) = self.event_task.take() {
             task.abort();
         }
+        self.stop_keepalive();
+    }
+}
+
+/// Server URI from a Server-mode server field, accepting `pbx`,
+/// `sips:pbx:5061;transport=tls` and `sip:1000@pbx` alike (an explicitly
+/// chosen Server mode may be given the account URI rather than the bare
+/// host). The user part is dropped.
+fn server_uri_of(server_uri: &str) -> Result<SipUri> {
+    let mut uri = sip_uri::dial_target(server_uri, None)
+        .map_err(|e| anyhow!("Invalid server address '{}': {}", server_uri.trim(), e))?;
+    uri.user = None;
+    uri.password = None;
+    Ok(uri)
+}
+
+/// The system resolver, or an empty stand-in when the OS DNS configuration
+/// cannot be read (rvoip then resolves hosts itself).
+fn default_resolver() -> Arc<dyn DnsResolver> {
+    match SystemResolver::new() {
+        Ok(resolver) => Arc::new(resolver),
+        Err(e) => {
+            warn!("System DNS configuration unavailable: {}", e);
+            Arc::new(StaticResolver::new())
+        }
+    }
+}
+
+/// Send [`SipEvent::Playback`] updates until playback `id` ends, is stopped
+/// or is replaced (e.g. by hold music), then a final one with no progress.
+async fn report_playback(injector: AudioInjector, id: u64, name: String, tx: mpsc::UnboundedSender<SipEvent>) {
+    let mut interval = tokio::time::interval(PLAYBACK_REPORT_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let progress = injector
+            .progress()
+            .filter(|progress| progress.id == id && !progress.finished);
+        let done = progress.is_none();
+        if tx.send(SipEvent::Playback { name: name.clone(), progress }).is_err() || done {
+            return;
+        }
+    }
+}
+
+/// Wait for the recording announcement `id` to end (or be stopped with the
+/// call's audio), then send a final [`SipEvent::Playback`] so hold music
+/// held back by it can start.
+async fn await_announcement(injector: AudioInjector, id: u64, tx: mpsc::UnboundedSender<SipEvent>) {
+    let mut interval = tokio::time::interval(PLAYBACK_REPORT_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if injector.progress().is_none_or(|progress| progress.id != id || progress.finished) {
+            let _ = tx.send(SipEvent::Playback { name: String::new(), progress: None });
+            return;
+        }
+    }
+}
+
+/// Keepalive loop for the registration flow.
+///
+/// CRLF pings only hold the NAT binding open (UDP gives no pong to check), so
+/// reachability is tracked in OPTIONS mode only: any final response counts as
+/// an answer, and `max_missed` consecutive misses flip the registrar to
+/// unreachable until the next answered ping.
+async fn run_keepalive(
+    control: PeerControl,
+    registrar: String,
+    config: KeepaliveConfig,
+    event_tx: Option<mpsc::UnboundedSender<SipEvent>>,
+) {
+    let interval = config.interval.max(Duration::from_secs(5));
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately; the REGISTER has just refreshed the binding.
+    ticker.tick().await;
+
+    let mut missed = 0u32;
+    let mut reachable = true;
+    loop {
+        ticker.tick().await;
+        match config.mode {
+            KeepaliveMode::Off => return,
+            KeepaliveMode::Crlf => {
+                if let Err(e) = control.send_keepalive(&registrar).await {
+                    debug!("CRLF keepalive to {} failed: {}", registrar, e);
+                }
+            }
+            KeepaliveMode::Options => {
+                let ping = control.options(registrar.clone()).send();
+                let answered = matches!(tokio::time::timeout(interval, ping).await, Ok(Ok(_)));
+                missed = if answered { 0 } else { missed + 1 };
+
+                let now_reachable = missed < config.max_missed.max(1);
+                if now_reachable != reachable {
+                    reachable = now_reachable;
+                    if reachable {
+                        info!("Registrar {} answering OPTIONS again", registrar);
+                    } else {
+                        warn!("Registrar {} missed {} OPTIONS keepalives", registrar, missed);
+                    }
+                    if let Some(tx) = &event_tx {
+                        let event = SipEvent::RegistrarReachability {
+                            registrar: registrar.clone(),
+                            reachable,
+                        };
+                        if tx.send(event).is_err() {
+                            return; // UI gone
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -668,23 +1726,59 @@ impl Drop for SipClientManager {
 /// inspection variants, etc.).
 fn translate_event(event: Event) -> Option<SipEvent> {
     Some(match event {
-        Event::IncomingCall { call_id, from, .. } => {
-            let display_name = parse_display_name(&from);
+        Event::IncomingCall {
+            call_id,
+            from,
+            headers,
+            sdp,
+            ..
+        } => {
+            let caller = CallerIdentity::from_headers(&from, &headers);
             SipEvent::IncomingCall {
                 call_id: call_id.to_string(),
                 from,
-                display_name,
+                caller,
+                srtp_offered: sdp.as_deref().map(srtp::sdp_offers_srtp),
             }
         }
         Event::CallProgress {
             call_id,
             status_code,
+            sdp,
             ..
-        } if (180..=189).contains(&status_code) => SipEvent::Ringing {
+        } if (180..=189).contains(&status_code) => {
+            // An answer in a provisional response means the network is
+            // sending audio (announcements, its own ringback)
+            if sdp.is_some() {
+                SipEvent::EarlyMedia {
+                    call_id: call_id.to_string(),
+                }
+            } else {
+                SipEvent::Ringing {
+                    call_id: call_id.to_string(),
+                }
+            }
+        }
+        Event::CallAnswered { call_id, .. } => SipEvent::Connected {
             call_id: call_id.to_string(),
         },
-        Event::CallAnswered { call_id, .. } => SipEvent::Connected {
+        Event::MediaNegotiated {
+            call_id,
+            codec,
+            payload_type,
+            clock_rate,
+            ptime,
+            srtp_suite,
+            ..
+        } => SipEvent::MediaNegotiated {
             call_id: call_id.to_string(),
+            media: NegotiatedMedia {
+                codec,
+                payload_type,
+                clock_rate,
+                ptime_ms: ptime,
+                srtp_suite,
+            },
         },
         Event::CallEnded { call_id, reason } => SipEvent::Ended {
             call_id: call_id.to_string(),
@@ -703,11 +1797,21 @@ fn translate_event(event: Event) -> Option<SipEvent> {
             code: status_code,
             reason,
         },
-        Event::CallOnHold { call_id } | Event::RemoteCallOnHold { call_id } => SipEvent::OnHold {
+        Event::CallOnHold { call_id } => SipEvent::OnHold {
+            call_id: call_id.to_string(),
+            remote: false,
+        },
+        Event::RemoteCallOnHold { call_id } => SipEvent::OnHold {
+            call_id: call_id.to_string(),
+            remote: true,
+        },
+        Event::CallResumed { call_id } => SipEvent::Resumed {
             call_id: call_id.to_string(),
+            remote: false,
         },
-        Event::CallResumed { call_id } | Event::RemoteCallResumed { call_id } => SipEvent::Resumed {
+        Event::RemoteCallResumed { call_id } => SipEvent::Resumed {
             call_id: call_id.to_string(),
+            remote: true,
         },
         Event::CallMuted { call_id } => SipEvent::Muted {
             call_id: call_id.to_string(),
@@ -750,26 +1854,132 @@ fn translate_event(event: Event) -> Option<SipEvent> {
             call_id: call_id.to_string(),
             reason,
         },
-        Event::RegistrationSuccess { registrar, .. } => SipEvent::Registered { registrar },
+        Event::RegistrationSuccess {
+            registrar,
+            service_route,
+            path,
+            ..
+        } => {
+            if !path.is_empty() {
+                // Path (RFC 3327) is the edge proxies' route back to us; the
+                // registrar applies it, we only note it for diagnostics.
+                info!("Registrar recorded Path: {:?}", path);
+            }
+            SipEvent::Registered {
+                registrar,
+                service_route,
+            }
+        }
         Event::RegistrationFailed {
-            registrar, reason, ..
-        } => SipEvent::RegistrationFailed { registrar, reason },
+            registrar,
+            reason,
+            status_code,
+            ..
+        } => SipEvent::RegistrationFailed {
+            registrar,
+            code: status_code,
+            reason,
+        },
+        Event::MediaQuality {
+            call_id,
+            packets_sent,
+            packets_received,
+            packets_lost,
+            jitter_ms,
+            round_trip_ms,
+            ..
+        } => SipEvent::MediaStats {
+            call_id: call_id.to_string(),
+            stats: MediaStats {
+                packets_sent,
+                packets_received,
+                packets_lost,
+                jitter_ms,
+                rtt_ms: round_trip_ms,
+                // Filled in by the coroutine, which knows the call's media
+                codec: None,
+                jitter_buffer_ms: None,
+            },
+        },
+        Event::MessageTrace {
+            call_id,
+            outbound,
+            local_addr,
+            remote_addr,
+            transport,
+            message,
+            ..
+        } => SipEvent::Trace(SipMessage {
+            at: chrono::Utc::now(),
+            direction: if outbound {
+                TraceDirection::Sent
+            } else {
+                TraceDirection::Received
+            },
+            local: local_addr.to_string(),
+            remote: remote_addr.to_string(),
+            transport: transport.to_string(),
+            raw: message,
+            session_id: call_id.map(|id| id.to_string()),
+        }),
         Event::NetworkError { error, .. } => SipEvent::Error { message: error },
-        // Everything else (NOTIFY, traces, detailed/inspection variants,
-        // media-quality, session-timer refreshes, etc.) is not surfaced to the UI.
+        // Everything else (NOTIFY, detailed/inspection variants,
+        // session-timer refreshes, etc.) is not surfaced to the UI.
         _ => return None,
     })
 }
 
-/// Best-effort extraction of a display name from a SIP From value such as
-/// `"Alice" <sip:alice@host>`.
-fn parse_display_name(from: &str) -> Option<String> {
-    let trimmed = from.trim();
-    if let Some(end_quote_rel) = trimmed.strip_prefix('"').and_then(|rest| rest.find('"')) {
-        let name = &trimmed[1..=end_quote_rel];
-        if !name.is_empty() {
-            return Some(name.to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sip_resolver::SrvRecord;
+
+    fn server_config(server_uri: &str) -> SipConfig {
+        SipConfig {
+            connection_mode: ConnectionMode::Server {
+                server_uri: server_uri.to_string(),
+                username: "200".to_string(),
+                password: "secret".to_string(),
+                auth_username: None,
+                domain: None,
+                realm: None,
+                display_name: None,
+            },
+            ..Default::default()
         }
     }
-    None
+
+    #[tokio::test]
+    async fn registration_fails_over_through_srv_targets() {
+        let mut manager = SipClientManager::new(server_config("sip:example.com"));
+        manager.resolver = Arc::new(
+            StaticResolver::new()
+                .with_srv(
+                    "_sip._udp.example.com",
+                    SrvRecord { priority: 20, weight: 0, port: 5070, target: "backup.example.com".to_string() },
+                )
+                .with_srv(
+                    "_sip._udp.example.com",
+                    SrvRecord { priority: 10, weight: 0, port: 5060, target: "primary.example.com".to_string() },
+                )
+                .with_ip("primary.example.com", "192.0.2.1".parse().unwrap())
+                .with_ip("backup.example.com", "192.0.2.2".parse().unwrap()),
+        );
+        manager.registration = manager.build_config().unwrap().1;
+        manager.registrar_targets = manager.resolve_first_hop().await;
+
+        let loose_route = manager.config.proxy.loose_route;
+        let hops: Vec<String> = manager.registrar_targets.iter().map(|t| t.route_uri(loose_route)).collect();
+        assert_eq!(hops.len(), 2);
+        assert!(hops[0].starts_with("sip:192.0.2.1:5060"), "{}", hops[0]);
+        assert!(hops[1].starts_with("sip:192.0.2.2:5070"), "{}", hops[1]);
+
+        assert_eq!(manager.next_hop().as_ref(), Some(&hops[0]));
+        // e.g. REGISTER timed out or got 503
+        assert!(manager.fail_over_registration().await);
+        assert_eq!(manager.next_hop().as_ref(), Some(&hops[1]));
+        // Every target tried
+        assert!(!manager.fail_over_registration().await);
+        assert_eq!(manager.next_hop().as_ref(), Some(&hops[1]));
+    }
 }
@@ -0,0 +1,63 @@
+//! User-supplied extra SIP headers (`X-Account-Code`, `Alert-Info`, ...).
+//!
+//! Headers are entered one per line as `Name: value`. Names must be RFC 3261
+//! tokens, and headers the stack builds itself (dialog, transaction and
+//! routing headers) are refused so a typo cannot break the request.
+
+/// Headers rvoip generates; overriding them would corrupt the dialog.
+const RESERVED: &[&str] = &[
+    "via", "v", "from", "f", "to", "t", "call-id", "i", "cseq", "contact", "m",
+    "content-length", "l", "content-type", "c", "max-forwards", "route", "record-route",
+    "expires", "authorization", "proxy-authorization",
+];
+
+pub type Header = (String, String);
+
+/// Parse `Name: value` lines, skipping blanks. Errors name the bad line.
+pub fn parse_lines(text: &str) -> Result<Vec<Header>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+/// Render headers back to the one-per-line form [`parse_lines`] accepts.
+pub fn format_lines(headers: &[Header]) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_line(line: &str) -> Result<Header, String> {
+    let (name, value) = line
+        .split_once(':')
+        .ok_or_else(|| format!("'{}' is not a 'Name: value' header", line))?;
+    let name = name.trim();
+    let value = value.trim();
+    if name.is_empty() || !name.chars().all(is_token_char) {
+        return Err(format!("'{}' is not a valid header name", name));
+    }
+    if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(name)) {
+        return Err(format!("{} is set by the SIP stack and cannot be overridden", name));
+    }
+    if value.is_empty() {
+        return Err(format!("{} has no value", name));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// `base` with every header named in `overrides` replaced by the override.
+pub fn merge(base: &[Header], overrides: &[Header]) -> Vec<Header> {
+    base.iter()
+        .filter(|(name, _)| !overrides.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)))
+        .chain(overrides)
+        .cloned()
+        .collect()
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-.!%*_+`'~".contains(c)
+}
@@ -0,0 +1,604 @@
+//! RFC 3263 server location: NAPTR → SRV → A/AAAA.
+//!
+//! Turns a registrar or proxy host into an ordered list of concrete
+//! [`SipTarget`]s so the client can fail over to the next server when one
+//! times out or answers 503. DNS access goes through the [`DnsResolver`]
+//! trait; [`SystemResolver`] uses the OS configuration via hickory, and
+//! [`StaticResolver`] serves in-memory records (none when the OS
+//! configuration is unreadable; canned zones in the tests).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use log::{debug, info};
+use rand::Rng;
+
+use crate::sip_uri::{Scheme, SipUri};
+
+/// SIP transport a target is reached over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Udp,
+    Tcp,
+    Tls,
+}
+
+impl Transport {
+    /// Parse a `transport=` URI parameter value.
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "udp" => Some(Self::Udp),
+            "tcp" => Some(Self::Tcp),
+            "tls" => Some(Self::Tls),
+            _ => None,
+        }
+    }
+
+    /// NAPTR service field for this transport (RFC 3263 §4.1).
+    fn naptr_service(self) -> &'static str {
+        match self {
+            Self::Udp => "SIP+D2U",
+            Self::Tcp => "SIP+D2T",
+            Self::Tls => "SIPS+D2T",
+        }
+    }
+
+    /// SRV owner-name prefix for this transport.
+    fn srv_prefix(self) -> &'static str {
+        match self {
+            Self::Udp => "_sip._udp",
+            Self::Tcp => "_sip._tcp",
+            Self::Tls => "_sips._tcp",
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Tls => 5061,
+            Self::Udp | Self::Tcp => 5060,
+        }
+    }
+
+    fn as_param(self) -> &'static str {
+        match self {
+            Self::Udp => "udp",
+            Self::Tcp => "tcp",
+            Self::Tls => "tls",
+        }
+    }
+}
+
+/// Transports we try, in preference order, when neither the URI nor NAPTR
+/// records pick one.
+const TRANSPORT_PREFERENCE: [Transport; 3] = [Transport::Udp, Transport::Tcp, Transport::Tls];
+
+/// A NAPTR record, reduced to the fields RFC 3263 uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaptrRecord {
+    pub order: u16,
+    pub preference: u16,
+    pub flags: String,
+    pub service: String,
+    pub replacement: String,
+}
+
+/// An SRV record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+/// DNS lookups needed for RFC 3263. Failed or empty lookups both mean "no
+/// records" to the algorithm, so implementations may return either.
+#[async_trait]
+pub trait DnsResolver: Send + Sync {
+    async fn naptr(&self, name: &str) -> Result<Vec<NaptrRecord>>;
+    async fn srv(&self, name: &str) -> Result<Vec<SrvRecord>>;
+    async fn ip(&self, host: &str) -> Result<Vec<IpAddr>>;
+}
+
+/// One concrete server to try.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SipTarget {
+    pub addr: SocketAddr,
+    pub transport: Transport,
+    /// Host name the address was resolved from (for logs and TLS SNI).
+    pub host: String,
+}
+
+impl SipTarget {
+    /// The target as a route URI, e.g. `sip:192.0.2.10:5060;transport=udp;lr`.
+    /// TLS targets keep their host name
+    /// (`sips:sip1.example.com:5061;transport=tls`) so the server
+    /// certificate is checked against it rather than an IP literal.
+    pub fn route_uri(&self, loose_route: bool) -> String {
+        let mut uri = SipUri::sip(self.addr.ip().to_string());
+        if self.transport == Transport::Tls {
+            uri.scheme = Scheme::Sips;
+            if !self.host.is_empty() {
+                uri.host = self.host.clone();
+            }
+        }
+        uri.port = Some(self.addr.port());
+        uri.set_param("transport", Some(self.transport.as_param()));
+        if loose_route {
+            uri.set_param("lr", None);
+        }
+        uri.to_string()
+    }
+}
+
+impl fmt::Display for SipTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}/{})", self.addr, self.host, self.transport.as_param())
+    }
+}
+
+/// Whether a failed request should be retried at the next target: RFC 3263
+/// §4.3 fails over on transport errors and timeouts (no response, 408) and on
+/// 503 Service Unavailable.
+pub fn is_failover_status(code: Option<u16>) -> bool {
+    matches!(code, None | Some(408) | Some(503))
+}
+
+/// Resolve `host` to an ordered failover list (RFC 3263 §4).
+///
+/// * IP literals are used as-is.
+/// * An explicit `port` skips NAPTR/SRV and only looks up A/AAAA.
+/// * Otherwise NAPTR picks transports (restricted to `transport` when the URI
+///   named one), SRV orders servers by priority and weight, and plain A/AAAA
+///   on the default port is the last resort.
+pub async fn resolve(
+    resolver: &dyn DnsResolver,
+    host: &str,
+    port: Option<u16>,
+    transport: Option<Transport>,
+) -> Result<Vec<SipTarget>> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        let transport = transport.unwrap_or(Transport::Udp);
+        let port = port.unwrap_or(transport.default_port());
+        return Ok(vec![SipTarget {
+            addr: SocketAddr::new(ip, port),
+            transport,
+            host: host.to_string(),
+        }]);
+    }
+
+    if let Some(port) = port {
+        let transport = transport.unwrap_or(Transport::Udp);
+        return address_targets(resolver, host, port, transport).await;
+    }
+
+    // NAPTR → SRV, in NAPTR order.
+    let mut targets = Vec::new();
+    for (transport, srv_name) in naptr_services(resolver, host, transport).await {
+        targets.extend(srv_targets(resolver, &srv_name, transport).await);
+    }
+
+    // No usable NAPTR: query SRV directly in our own transport preference.
+    if targets.is_empty() {
+        let transports: Vec<Transport> = match transport {
+            Some(t) => vec![t],
+            None => TRANSPORT_PREFERENCE.to_vec(),
+        };
+        for transport in transports {
+            let srv_name = format!("{}.{}", transport.srv_prefix(), host);
+            targets.extend(srv_targets(resolver, &srv_name, transport).await);
+            if !targets.is_empty() {
+                break;
+            }
+        }
+    }
+
+    if targets.is_empty() {
+        let transport = transport.unwrap_or(Transport::Udp);
+        targets = address_targets(resolver, host, transport.default_port(), transport).await?;
+    }
+
+    dedup(&mut targets);
+    info!(
+        "Resolved {} to {}",
+        host,
+        targets.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+    );
+    Ok(targets)
+}
+
+/// NAPTR lookup filtered to SIP services we support, as `(transport, srv name)`
+/// in order/preference order.
+async fn naptr_services(
+    resolver: &dyn DnsResolver,
+    host: &str,
+    only: Option<Transport>,
+) -> Vec<(Transport, String)> {
+    let mut records = resolver.naptr(host).await.unwrap_or_else(|e| {
+        debug!("NAPTR {} failed: {}", host, e);
+        Vec::new()
+    });
+    records.sort_by_key(|r| (r.order, r.preference));
+
+    records
+        .into_iter()
+        .filter(|r| r.flags.eq_ignore_ascii_case("s"))
+        .filter_map(|r| {
+            let transport = TRANSPORT_PREFERENCE
+                .into_iter()
+                .find(|t| r.service.eq_ignore_ascii_case(t.naptr_service()))?;
+            if only.is_some_and(|o| o != transport) {
+                return None;
+            }
+            Some((transport, r.replacement.trim_end_matches('.').to_string()))
+        })
+        .collect()
+}
+
+/// SRV lookup of `name`, each record expanded to its addresses, in RFC 2782
+/// priority/weight order.
+async fn srv_targets(resolver: &dyn DnsResolver, name: &str, transport: Transport) -> Vec<SipTarget> {
+    let records = resolver.srv(name).await.unwrap_or_else(|e| {
+        debug!("SRV {} failed: {}", name, e);
+        Vec::new()
+    });
+    // A lone "." target means the service is explicitly not offered.
+    let records: Vec<SrvRecord> = records
+        .into_iter()
+        .filter(|r| !r.target.is_empty() && r.target != ".")
+        .collect();
+
+    let mut targets = Vec::new();
+    for record in order_srv(records) {
+        let host = record.target.trim_end_matches('.').to_string();
+        match address_targets(resolver, &host, record.port, transport).await {
+            Ok(found) => targets.extend(found),
+            Err(e) => debug!("A/AAAA {} failed: {}", host, e),
+        }
+    }
+    targets
+}
+
+async fn address_targets(
+    resolver: &dyn DnsResolver,
+    host: &str,
+    port: u16,
+    transport: Transport,
+) -> Result<Vec<SipTarget>> {
+    Ok(resolver
+        .ip(host)
+        .await?
+        .into_iter()
+        .map(|ip| SipTarget {
+            addr: SocketAddr::new(ip, port),
+            transport,
+            host: host.to_string(),
+        })
+        .collect())
+}
+
+/// Order SRV records per RFC 2782: ascending priority, and within a priority
+/// a weighted random permutation (zero-weight records still get a chance).
+pub fn order_srv(mut records: Vec<SrvRecord>) -> Vec<SrvRecord> {
+    records.sort_by_key(|r| r.priority);
+    let mut rng = rand::thread_rng();
+    let mut ordered = Vec::with_capacity(records.len());
+
+    while !records.is_empty() {
+        let priority = records[0].priority;
+        let split = records.iter().take_while(|r| r.priority == priority).count();
+        let mut group: Vec<SrvRecord> = records.drain(..split).collect();
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|r| u32::from(r.weight) + 1).sum();
+            let mut pick = rng.gen_range(0..total);
+            let index = group
+                .iter()
+                .position(|r| {
+                    let w = u32::from(r.weight) + 1;
+                    if pick < w {
+                        true
+                    } else {
+                        pick -= w;
+                        false
+                    }
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index));
+        }
+    }
+    ordered
+}
+
+fn dedup(targets: &mut Vec<SipTarget>) {
+    let mut seen = Vec::new();
+    targets.retain(|t| {
+        let key = (t.addr, t.transport);
+        if seen.contains(&key) {
+            false
+        } else {
+            seen.push(key);
+            true
+        }
+    });
+}
+
+/// [`DnsResolver`] backed by the system resolver configuration.
+pub struct SystemResolver {
+    inner: TokioAsyncResolver,
+}
+
+impl SystemResolver {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            inner: TokioAsyncResolver::tokio_from_system_conf()?,
+        })
+    }
+}
+
+#[async_trait]
+impl DnsResolver for SystemResolver {
+    async fn naptr(&self, name: &str) -> Result<Vec<NaptrRecord>> {
+        let lookup = self.inner.lookup(name, RecordType::NAPTR).await?;
+        Ok(lookup
+            .iter()
+            .filter_map(|rdata| match rdata {
+                RData::NAPTR(naptr) => Some(NaptrRecord {
+                    order: naptr.order(),
+                    preference: naptr.preference(),
+                    flags: String::from_utf8_lossy(naptr.flags()).into_owned(),
+                    service: String::from_utf8_lossy(naptr.services()).into_owned(),
+                    replacement: naptr.replacement().to_utf8(),
+                }),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn srv(&self, name: &str) -> Result<Vec<SrvRecord>> {
+        let lookup = self.inner.srv_lookup(name).await?;
+        Ok(lookup
+            .iter()
+            .map(|srv| SrvRecord {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: srv.target().to_utf8(),
+            })
+            .collect())
+    }
+
+    async fn ip(&self, host: &str) -> Result<Vec<IpAddr>> {
+        Ok(self.inner.lookup_ip(host).await?.iter().collect())
+    }
+}
+
+/// [`DnsResolver`] answering from in-memory records: the empty fallback when
+/// the system resolver is unavailable, and a fake zone for tests.
+#[derive(Debug, Clone, Default)]
+pub struct StaticResolver {
+    naptr: HashMap<String, Vec<NaptrRecord>>,
+    srv: HashMap<String, Vec<SrvRecord>>,
+    ip: HashMap<String, Vec<IpAddr>>,
+}
+
+impl StaticResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+impl StaticResolver {
+    pub fn with_naptr(mut self, name: &str, record: NaptrRecord) -> Self {
+        self.naptr.entry(key(name)).or_default().push(record);
+        self
+    }
+
+    pub fn with_srv(mut self, name: &str, record: SrvRecord) -> Self {
+        self.srv.entry(key(name)).or_default().push(record);
+        self
+    }
+
+    pub fn with_ip(mut self, host: &str, ip: IpAddr) -> Self {
+        self.ip.entry(key(host)).or_default().push(ip);
+        self
+    }
+}
+
+#[async_trait]
+impl DnsResolver for StaticResolver {
+    async fn naptr(&self, name: &str) -> Result<Vec<NaptrRecord>> {
+        Ok(self.naptr.get(&key(name)).cloned().unwrap_or_default())
+    }
+
+    async fn srv(&self, name: &str) -> Result<Vec<SrvRecord>> {
+        Ok(self.srv.get(&key(name)).cloned().unwrap_or_default())
+    }
+
+    async fn ip(&self, host: &str) -> Result<Vec<IpAddr>> {
+        Ok(self.ip.get(&key(host)).cloned().unwrap_or_default())
+    }
+}
+
+fn key(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn naptr(order: u16, service: &str, replacement: &str) -> NaptrRecord {
+        NaptrRecord {
+            order,
+            preference: 10,
+            flags: "s".to_string(),
+            service: service.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    fn srv(priority: u16, weight: u16, port: u16, target: &str) -> SrvRecord {
+        SrvRecord {
+            priority,
+            weight,
+            port,
+            target: target.to_string(),
+        }
+    }
+
+    fn addrs(targets: &[SipTarget]) -> Vec<(String, Transport)> {
+        targets.iter().map(|t| (t.addr.to_string(), t.transport)).collect()
+    }
+
+    #[tokio::test]
+    async fn ip_literal_is_used_as_is() {
+        let resolver = StaticResolver::new();
+        let targets = resolve(&resolver, "[2001:db8::1]", None, Some(Transport::Tls)).await.unwrap();
+        assert_eq!(addrs(&targets), [("[2001:db8::1]:5061".to_string(), Transport::Tls)]);
+    }
+
+    #[tokio::test]
+    async fn naptr_orders_transports_then_srv_then_addresses() {
+        let resolver = StaticResolver::new()
+            .with_naptr("example.com", naptr(20, "SIP+D2U", "_sip._udp.example.com."))
+            .with_naptr("example.com", naptr(10, "SIP+D2T", "_sip._tcp.example.com."))
+            .with_naptr("example.com", naptr(5, "E2U+sip", "ignored.example.com."))
+            .with_srv("_sip._tcp.example.com", srv(10, 0, 5060, "tcp.example.com."))
+            .with_srv("_sip._udp.example.com", srv(10, 0, 5070, "udp.example.com."))
+            .with_ip("tcp.example.com", ip("192.0.2.1"))
+            .with_ip("udp.example.com", ip("192.0.2.2"))
+            .with_ip("example.com", ip("192.0.2.99"));
+
+        let targets = resolve(&resolver, "example.com", None, None).await.unwrap();
+        assert_eq!(
+            addrs(&targets),
+            [
+                ("192.0.2.1:5060".to_string(), Transport::Tcp),
+                ("192.0.2.2:5070".to_string(), Transport::Udp),
+            ]
+        );
+
+        // transport=udp in the URI restricts NAPTR to that service
+        let targets = resolve(&resolver, "example.com", None, Some(Transport::Udp)).await.unwrap();
+        assert_eq!(addrs(&targets), [("192.0.2.2:5070".to_string(), Transport::Udp)]);
+    }
+
+    #[tokio::test]
+    async fn srv_without_naptr_follows_transport_preference() {
+        let resolver = StaticResolver::new()
+            .with_srv("_sip._tcp.example.com", srv(10, 0, 5060, "sip.example.com"))
+            .with_srv("_sips._tcp.example.com", srv(10, 0, 5061, "sip.example.com"))
+            .with_ip("sip.example.com", ip("192.0.2.1"));
+
+        let targets = resolve(&resolver, "example.com", None, None).await.unwrap();
+        assert_eq!(addrs(&targets), [("192.0.2.1:5060".to_string(), Transport::Tcp)]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_addresses_on_the_default_port() {
+        let resolver = StaticResolver::new()
+            .with_ip("example.com", ip("192.0.2.1"))
+            .with_ip("example.com", ip("192.0.2.2"));
+
+        let targets = resolve(&resolver, "Example.COM.", None, None).await.unwrap();
+        assert_eq!(
+            addrs(&targets),
+            [
+                ("192.0.2.1:5060".to_string(), Transport::Udp),
+                ("192.0.2.2:5060".to_string(), Transport::Udp),
+            ]
+        );
+
+        let targets = resolve(&resolver, "example.com", None, Some(Transport::Tls)).await.unwrap();
+        assert_eq!(targets[0].addr.port(), 5061);
+    }
+
+    #[tokio::test]
+    async fn explicit_port_skips_naptr_and_srv() {
+        let resolver = StaticResolver::new()
+            .with_srv("_sip._udp.example.com", srv(10, 0, 5070, "other.example.com"))
+            .with_ip("other.example.com", ip("192.0.2.9"))
+            .with_ip("example.com", ip("192.0.2.1"));
+
+        let targets = resolve(&resolver, "example.com", Some(5080), None).await.unwrap();
+        assert_eq!(addrs(&targets), [("192.0.2.1:5080".to_string(), Transport::Udp)]);
+    }
+
+    #[tokio::test]
+    async fn unoffered_service_and_duplicates_are_dropped() {
+        let resolver = StaticResolver::new()
+            .with_srv("_sip._udp.example.com", srv(10, 0, 5060, "."))
+            .with_srv("_sip._udp.example.com", srv(20, 0, 5060, "sip.example.com"))
+            .with_srv("_sip._udp.example.com", srv(30, 0, 5060, "sip.example.com"))
+            .with_ip("sip.example.com", ip("192.0.2.1"));
+
+        let targets = resolve(&resolver, "example.com", None, None).await.unwrap();
+        assert_eq!(addrs(&targets), [("192.0.2.1:5060".to_string(), Transport::Udp)]);
+    }
+
+    #[test]
+    fn srv_priority_is_strict() {
+        let records = vec![
+            srv(30, 1000, 5060, "c"),
+            srv(10, 0, 5060, "a"),
+            srv(20, 1000, 5060, "b"),
+            srv(10, 0, 5060, "a2"),
+        ];
+        for _ in 0..100 {
+            let priorities: Vec<u16> = order_srv(records.clone()).iter().map(|r| r.priority).collect();
+            assert_eq!(priorities, [10, 10, 20, 30]);
+        }
+    }
+
+    #[test]
+    fn srv_weight_biases_order_within_a_priority() {
+        // Selection odds are (weight + 1) / total: 4/5 for the heavy record,
+        // 1/5 for the zero-weight one.
+        let records = vec![srv(10, 0, 5060, "light"), srv(10, 3, 5060, "heavy")];
+        let runs = 2000;
+        let light_first = (0..runs)
+            .filter(|_| order_srv(records.clone())[0].target == "light")
+            .count();
+        assert!((200..600).contains(&light_first), "light first {} of {}", light_first, runs);
+    }
+
+    #[test]
+    fn tls_routes_name_the_server_host() {
+        let target = |transport, host: &str| SipTarget {
+            addr: "192.0.2.10:5061".parse().unwrap(),
+            transport,
+            host: host.to_string(),
+        };
+        assert_eq!(
+            target(Transport::Tls, "sip1.example.com").route_uri(true),
+            "sips:sip1.example.com:5061;transport=tls;lr"
+        );
+        assert_eq!(target(Transport::Tls, "192.0.2.10").route_uri(false), "sips:192.0.2.10:5061;transport=tls");
+        assert_eq!(
+            target(Transport::Tcp, "sip1.example.com").route_uri(false),
+            "sip:192.0.2.10:5061;transport=tcp"
+        );
+    }
+
+    #[test]
+    fn fails_over_on_timeout_and_503_only() {
+        assert!(is_failover_status(None));
+        assert!(is_failover_status(Some(408)));
+        assert!(is_failover_status(Some(503)));
+        for code in [401, 403, 404, 486, 500, 504] {
+            assert!(!is_failover_status(Some(code)), "{}", code);
+        }
+    }
+}
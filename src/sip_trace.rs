@@ -0,0 +1,219 @@
+//! Ring buffer of raw SIP messages for the in-app trace viewer.
+//!
+//! rvoip reports every request/response it sends or receives; we keep the
+//! most recent ones with a timestamp and the remote address, and derive
+//! Call-ID grouping and a ladder (sequence) diagram from the raw text.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+
+use crate::pcap::CapturedPacket;
+
+/// Messages kept; a registered client idling with 30 s keepalives fills
+/// this in about a day, a busy call session much sooner.
+const MAX_MESSAGES: usize = 2000;
+
+/// Local column label in the ladder diagram.
+pub const LOCAL: &str = "This phone";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SipMessage {
+    pub at: DateTime<Utc>,
+    pub direction: TraceDirection,
+    /// Our `ip:port` for the flow.
+    pub local: String,
+    /// Remote `ip:port` the message went to or came from.
+    pub remote: String,
+    /// Transport name (`UDP`, `TCP`, `TLS`, `WS`).
+    pub transport: String,
+    /// Full message as sent on the wire (start line, headers, body).
+    pub raw: String,
+    /// rvoip session the message belongs to, when it is part of a call.
+    pub session_id: Option<String>,
+}
+
+impl SipMessage {
+    pub fn start_line(&self) -> &str {
+        self.raw.lines().next().unwrap_or("").trim()
+    }
+
+    pub fn is_request(&self) -> bool {
+        !self.start_line().starts_with("SIP/")
+    }
+
+    /// Value of the first header named `name` (or its compact form).
+    pub fn header(&self, name: &str, compact: Option<&str>) -> Option<&str> {
+        self.raw
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .find(|(n, _)| {
+                let n = n.trim();
+                n.eq_ignore_ascii_case(name) || compact.is_some_and(|c| n.eq_ignore_ascii_case(c))
+            })
+            .map(|(_, v)| v.trim())
+    }
+
+    pub fn call_id(&self) -> Option<&str> {
+        self.header("Call-ID", Some("i"))
+    }
+
+    /// Short label for the ladder: the method for requests, `200 OK`
+    /// (with the CSeq method) for responses.
+    pub fn summary(&self) -> String {
+        let line = self.start_line();
+        if self.is_request() {
+            return line.split_whitespace().next().unwrap_or(line).to_string();
+        }
+        let status = line.split_once(' ').map_or(line, |(_, status)| status);
+        match self.header("CSeq", None).and_then(|c| c.split_whitespace().nth(1)) {
+            Some(method) => format!("{} ({})", status, method),
+            None => status.to_string(),
+        }
+    }
+
+    /// The message as a UDP packet for pcap export; `None` when an address
+    /// is not `ip:port` (e.g. a WebSocket URL).
+    pub fn to_packet(&self) -> Option<CapturedPacket> {
+        let local = self.local.parse().ok()?;
+        let remote = self.remote.parse().ok()?;
+        let (src, dst) = match self.direction {
+            TraceDirection::Sent => (local, remote),
+            TraceDirection::Received => (remote, local),
+        };
+        Some(CapturedPacket {
+            at: self.at,
+            src,
+            dst,
+            payload: self.raw.as_bytes().to_vec(),
+            comment: (!self.transport.eq_ignore_ascii_case("UDP"))
+                .then(|| format!("Sent over {}, shown as UDP", self.transport)),
+        })
+    }
+
+    /// Text block for copy/export, in the usual `sngrep`/`ngrep` layout.
+    pub fn to_text(&self) -> String {
+        let arrow = match self.direction {
+            TraceDirection::Sent => "-->",
+            TraceDirection::Received => "<--",
+        };
+        format!(
+            "{} {} {} {}\n{}\n",
+            self.at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            self.transport,
+            arrow,
+            self.remote,
+            self.raw.replace("\r\n", "\n").trim_end()
+        )
+    }
+}
+
+/// One arrow of the ladder diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderRow {
+    pub at: DateTime<Utc>,
+    /// Column indexes into [`Ladder::endpoints`].
+    pub from: usize,
+    pub to: usize,
+    pub label: String,
+    pub is_request: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ladder {
+    /// Column headers: [`LOCAL`] first, then remotes in order of appearance.
+    pub endpoints: Vec<String>,
+    pub rows: Vec<LadderRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SipTrace {
+    messages: VecDeque<SipMessage>,
+}
+
+impl SipTrace {
+    pub fn push(&mut self, message: SipMessage) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    /// Distinct Call-IDs, most recent dialog first, each with the start
+    /// line of its first message (e.g. `INVITE sip:bob@...`).
+    pub fn dialogs(&self) -> Vec<(String, String)> {
+        let mut dialogs: Vec<(String, String)> = Vec::new();
+        for message in &self.messages {
+            if let Some(call_id) = message.call_id() {
+                if !dialogs.iter().any(|(id, _)| id == call_id) {
+                    dialogs.push((call_id.to_string(), message.start_line().to_string()));
+                }
+            }
+        }
+        dialogs.reverse();
+        dialogs
+    }
+
+    /// Messages of one dialog, or all of them, oldest first.
+    pub fn messages<'a>(&'a self, call_id: Option<&'a str>) -> impl Iterator<Item = &'a SipMessage> {
+        self.messages
+            .iter()
+            .filter(move |m| call_id.is_none() || m.call_id() == call_id)
+    }
+
+    pub fn ladder(&self, call_id: Option<&str>) -> Ladder {
+        let mut ladder = Ladder {
+            endpoints: vec![LOCAL.to_string()],
+            rows: Vec::new(),
+        };
+        for message in self.messages(call_id) {
+            let remote = match ladder.endpoints.iter().position(|e| *e == message.remote) {
+                Some(index) => index,
+                None => {
+                    ladder.endpoints.push(message.remote.clone());
+                    ladder.endpoints.len() - 1
+                }
+            };
+            let (from, to) = match message.direction {
+                TraceDirection::Sent => (0, remote),
+                TraceDirection::Received => (remote, 0),
+            };
+            ladder.rows.push(LadderRow {
+                at: message.at,
+                from,
+                to,
+                label: message.summary(),
+                is_request: message.is_request(),
+            });
+        }
+        ladder
+    }
+
+    /// Plain-text export of one dialog or the whole buffer.
+    pub fn to_text(&self, call_id: Option<&str>) -> String {
+        self.messages(call_id)
+            .map(SipMessage::to_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
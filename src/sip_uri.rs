@@ -0,0 +1,553 @@
+//! SIP/SIPS/tel URI and name-addr parsing (RFC 3261 §19.1, §25.1; RFC 3966).
+//!
+//! One parser and printer shared by dialing, transfer, proxy/registrar
+//! configuration and caller-ID display, so ports, parameters, `sips:`,
+//! `user=phone` and bracketed IPv6 hosts survive the trip from the UI into
+//! the INVITE. Parsing is deliberately lenient about whitespace and case but
+//! printing is canonical, so `parse(print(x)) == x` for every parsed value:
+//! display names are quoted unless they are a single token, and IPv6 hosts
+//! must be bracketed (`sip:[2001:db8::1]`, never `sip:2001:db8::1`).
+
+use std::fmt;
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UriError {
+    #[error("empty URI")]
+    Empty,
+    #[error("unsupported URI scheme '{0}'")]
+    UnsupportedScheme(String),
+    #[error("missing host in '{0}'")]
+    MissingHost(String),
+    #[error("invalid host '{0}'")]
+    InvalidHost(String),
+    #[error("invalid port '{0}'")]
+    InvalidPort(String),
+    #[error("unterminated {0}")]
+    Unterminated(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Sip,
+    Sips,
+    Tel,
+}
+
+impl Scheme {
+    fn parse(value: &str) -> Result<Self, UriError> {
+        match value.to_ascii_lowercase().as_str() {
+            "sip" => Ok(Self::Sip),
+            "sips" => Ok(Self::Sips),
+            "tel" => Ok(Self::Tel),
+            _ => Err(UriError::UnsupportedScheme(value.to_string())),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sip => "sip",
+            Self::Sips => "sips",
+            Self::Tel => "tel",
+        }
+    }
+}
+
+/// A `sip:`, `sips:` or `tel:` URI.
+///
+/// For `tel:` URIs the number is held in `user` and `host` is empty. The user
+/// part is kept exactly as written (escapes included) so it round-trips.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SipUri {
+    pub scheme: Scheme,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// Host name or IP; IPv6 literals are stored without brackets.
+    pub host: String,
+    pub port: Option<u16>,
+    /// URI parameters (`;transport=tcp`, `;lr`, `;user=phone`), in order.
+    pub params: Vec<(String, Option<String>)>,
+    /// URI headers (`?Replaces=...`), in order.
+    pub headers: Vec<(String, String)>,
+}
+
+impl SipUri {
+    /// A bare `sip:host` URI.
+    pub fn sip(host: impl Into<String>) -> Self {
+        Self {
+            scheme: Scheme::Sip,
+            user: None,
+            password: None,
+            host: host.into(),
+            port: None,
+            params: Vec::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// A `tel:` URI for `number` (global `+...` or local with context params).
+    pub fn tel(number: impl Into<String>) -> Self {
+        Self {
+            scheme: Scheme::Tel,
+            user: Some(number.into()),
+            password: None,
+            host: String::new(),
+            port: None,
+            params: Vec::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(UriError::Empty);
+        }
+        let (scheme, rest) = input
+            .split_once(':')
+            .ok_or_else(|| UriError::UnsupportedScheme(String::new()))?;
+        let scheme = Scheme::parse(scheme.trim())?;
+
+        let (rest, headers) = match rest.split_once('?') {
+            Some((rest, headers)) => (rest, parse_headers(headers)),
+            None => (rest, Vec::new()),
+        };
+
+        if scheme == Scheme::Tel {
+            let mut parts = rest.split(';');
+            let number = parts.next().unwrap_or_default().trim();
+            if number.is_empty() {
+                return Err(UriError::Empty);
+            }
+            let mut uri = Self::tel(number);
+            uri.params = parse_params(parts);
+            uri.headers = headers;
+            return Ok(uri);
+        }
+
+        // '@' may not appear unescaped after the userinfo, but ';' may appear
+        // inside it (sip:alice;day=tuesday@host), so split on the last '@'.
+        let (userinfo, hostpart) = match rest.rsplit_once('@') {
+            Some((userinfo, hostpart)) => (Some(userinfo), hostpart),
+            None => (None, rest),
+        };
+        let (user, password) = match userinfo {
+            Some(info) => match info.split_once(':') {
+                Some((user, password)) => (Some(user.to_string()), Some(password.to_string())),
+                None => (Some(info.to_string()), None),
+            },
+            None => (None, None),
+        };
+        if user.as_deref() == Some("") {
+            return Err(UriError::InvalidHost(input.to_string()));
+        }
+
+        let mut parts = hostpart.split(';');
+        let hostport = parts.next().unwrap_or_default().trim();
+        let (host, port) = parse_hostport(hostport, input)?;
+
+        Ok(Self {
+            scheme,
+            user,
+            password,
+            host,
+            port,
+            params: parse_params(parts),
+            headers,
+        })
+    }
+
+    /// Value of URI parameter `name` (case-insensitive); `Some(None)` for a
+    /// flag parameter such as `lr`.
+    pub fn param(&self, name: &str) -> Option<Option<&str>> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_deref())
+    }
+
+    /// Set (or replace) a URI parameter.
+    pub fn set_param(&mut self, name: &str, value: Option<&str>) {
+        let value = value.map(str::to_string);
+        match self.params.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(existing) => existing.1 = value,
+            None => self.params.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn remove_param(&mut self, name: &str) {
+        self.params.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    }
+
+    /// Host formatted for a URI: IPv6 literals in brackets.
+    pub fn host_for_uri(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        }
+    }
+
+    /// `host[:port]` as it appears in the URI.
+    pub fn host_port(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{}", self.host_for_uri(), port),
+            None => self.host_for_uri(),
+        }
+    }
+
+    /// `true` for telephone-number URIs: `tel:` or `sip:...;user=phone`.
+    pub fn is_phone_number(&self) -> bool {
+        self.scheme == Scheme::Tel
+            || self
+                .param("user")
+                .flatten()
+                .is_some_and(|v| v.eq_ignore_ascii_case("phone"))
+    }
+
+    /// The URI without the scheme (`alice@host:5060`), as typed in dial boxes.
+    pub fn without_scheme(&self) -> String {
+        let full = self.to_string();
+        full.split_once(':').map_or(full.clone(), |(_, rest)| rest.to_string())
+    }
+}
+
+impl fmt::Display for SipUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.scheme.as_str())?;
+        if self.scheme == Scheme::Tel {
+            write!(f, "{}", self.user.as_deref().unwrap_or_default())?;
+        } else {
+            if let Some(user) = &self.user {
+                write!(f, "{}", user)?;
+                if let Some(password) = &self.password {
+                    write!(f, ":{}", password)?;
+                }
+                write!(f, "@")?;
+            }
+            write!(f, "{}", self.host_port())?;
+        }
+        for (name, value) in &self.params {
+            match value {
+                Some(value) => write!(f, ";{}={}", name, value)?,
+                None => write!(f, ";{}", name)?,
+            }
+        }
+        for (i, (name, value)) in self.headers.iter().enumerate() {
+            let sep = if i == 0 { '?' } else { '&' };
+            write!(f, "{}{}={}", sep, name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A name-addr (`"Bob" <sip:200@pbx>;tag=1`) or bare addr-spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameAddr {
+    pub display_name: Option<String>,
+    pub uri: SipUri,
+    /// Header parameters after the closing `>` (`;tag=...`).
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl NameAddr {
+    pub fn new(uri: SipUri) -> Self {
+        Self {
+            display_name: None,
+            uri,
+            params: Vec::new(),
+        }
+    }
+
+    /// Parse a header value such as a From/P-Asserted-Identity, or a dial
+    /// target. A bare addr-spec keeps its `;params` on the URI.
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(UriError::Empty);
+        }
+
+        let (display_name, rest) = if let Some(quoted) = input.strip_prefix('"') {
+            let (name, rest) = parse_quoted(quoted)?;
+            (Some(name), rest.trim_start())
+        } else if let Some(open) = input.find('<') {
+            let name = input[..open].trim();
+            ((!name.is_empty()).then(|| name.to_string()), &input[open..])
+        } else {
+            return Ok(Self::new(SipUri::parse(input)?));
+        };
+
+        let inner = rest
+            .strip_prefix('<')
+            .ok_or(UriError::Unterminated("name-addr"))?;
+        let (uri, tail) = inner
+            .split_once('>')
+            .ok_or(UriError::Unterminated("'<'"))?;
+        let mut tail = tail.trim().split(';');
+        tail.next(); // text before the first ';' (normally empty)
+
+        Ok(Self {
+            display_name: display_name.filter(|n| !n.is_empty()),
+            uri: SipUri::parse(uri)?,
+            params: parse_params(tail),
+        })
+    }
+}
+
+impl fmt::Display for NameAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bare = self.display_name.is_none()
+            && self.params.is_empty()
+            && self.uri.params.is_empty()
+            && self.uri.headers.is_empty();
+        if bare {
+            return write!(f, "{}", self.uri);
+        }
+        if let Some(name) = &self.display_name {
+            if !name.is_empty() && name.chars().all(is_token_char) {
+                write!(f, "{} ", name)?;
+            } else {
+                write!(f, "\"{}\" ", name.replace('\\', "\\\\").replace('"', "\\\""))?;
+            }
+        }
+        write!(f, "<{}>", self.uri)?;
+        for (name, value) in &self.params {
+            match value {
+                Some(value) => write!(f, ";{}={}", name, value)?,
+                None => write!(f, ";{}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Turn whatever was typed into a dial or transfer box into a request URI.
+///
+/// Accepts full URIs and name-addrs (`"Bob" <sip:200@pbx:5080;transport=tcp>`),
+/// `user@host` without a scheme, and bare extensions, which are placed on
+/// `default_host` (`host[:port]`) when one is given and otherwise treated as
+/// a host name.
+pub fn dial_target(input: &str, default_host: Option<&str>) -> Result<SipUri, UriError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(UriError::Empty);
+    }
+    if has_scheme(input) || input.contains('<') {
+        return Ok(NameAddr::parse(input)?.uri);
+    }
+    if input.contains('@') {
+        return SipUri::parse(&format!("sip:{}", input));
+    }
+    match default_host.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(host) => {
+            let mut uri = dial_target(host, None)?;
+            let (user, params) = match input.split_once(';') {
+                Some((user, params)) => (user, parse_params(params.split(';'))),
+                None => (input, Vec::new()),
+            };
+            uri.user = Some(user.to_string());
+            uri.password = None;
+            for (name, value) in params {
+                uri.set_param(&name, value.as_deref());
+            }
+            Ok(uri)
+        }
+        None => SipUri::parse(&format!("sip:{}", input)),
+    }
+}
+
+fn has_scheme(input: &str) -> bool {
+    input
+        .split_once(':')
+        .is_some_and(|(scheme, _)| Scheme::parse(scheme.trim()).is_ok())
+}
+
+fn parse_hostport(hostport: &str, input: &str) -> Result<(String, Option<u16>), UriError> {
+    let (host, port) = if let Some(v6) = hostport.strip_prefix('[') {
+        let (host, tail) = v6
+            .split_once(']')
+            .ok_or(UriError::Unterminated("IPv6 reference"))?;
+        if host.parse::<std::net::Ipv6Addr>().is_err() {
+            return Err(UriError::InvalidHost(host.to_string()));
+        }
+        match tail {
+            "" => (host, None),
+            tail => (host, Some(tail.strip_prefix(':').unwrap_or(tail))),
+        }
+    } else {
+        match hostport.split_once(':') {
+            // More than one colon: an IPv6 literal without brackets, whose
+            // port (if any) cannot be told from the address.
+            Some((_, port)) if port.contains(':') => return Err(UriError::InvalidHost(hostport.to_string())),
+            Some((host, port)) => (host, Some(port)),
+            None => (hostport, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(UriError::MissingHost(input.to_string()));
+    }
+    let is_v6 = host.contains(':');
+    if !is_v6 && !host.chars().all(|c| c.is_ascii_alphanumeric() || "-._".contains(c)) {
+        return Err(UriError::InvalidHost(host.to_string()));
+    }
+    let port = match port {
+        Some(port) => Some(
+            port.parse::<u16>()
+                .map_err(|_| UriError::InvalidPort(port.to_string()))?,
+        ),
+        None => None,
+    };
+    Ok((host.to_string(), port))
+}
+
+fn parse_params<'a>(parts: impl Iterator<Item = &'a str>) -> Vec<(String, Option<String>)> {
+    parts
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.split_once('=') {
+            Some((name, value)) => (name.trim().to_string(), Some(value.trim().to_string())),
+            None => (p.to_string(), None),
+        })
+        .collect()
+}
+
+fn parse_headers(headers: &str) -> Vec<(String, String)> {
+    headers
+        .split('&')
+        .filter(|h| !h.is_empty())
+        .map(|h| match h.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (h.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// Parse a quoted-string body (after the opening quote), returning the
+/// unescaped text and the remainder after the closing quote.
+fn parse_quoted(input: &str) -> Result<(String, &str), UriError> {
+    let mut out = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+            }
+            '"' => return Ok((out, &input[i + 1..])),
+            c => out.push(c),
+        }
+    }
+    Err(UriError::Unterminated("quoted display name"))
+}
+
+/// RFC 3261 `token` characters. A display name made of one token is printed
+/// unquoted; anything else (spaces included, which parsing would trim or
+/// fold) is quoted.
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-.!%*_+`'~".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::option;
+    use proptest::prelude::*;
+
+    fn params() -> impl Strategy<Value = Vec<(String, Option<String>)>> {
+        prop::collection::vec(("[a-z][a-z-]{0,7}", option::of("[A-Za-z0-9.+-]{0,8}")), 0..4)
+    }
+
+    fn host() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[a-z0-9]([a-z0-9-]{0,6}[a-z0-9])?(\\.[a-z]{2,6}){0,3}",
+            any::<std::net::Ipv4Addr>().prop_map(|ip| ip.to_string()),
+            any::<std::net::Ipv6Addr>().prop_map(|ip| ip.to_string()),
+        ]
+    }
+
+    fn sip_uri() -> impl Strategy<Value = SipUri> {
+        (
+            prop_oneof![Just(Scheme::Sip), Just(Scheme::Sips)],
+            option::of(("[A-Za-z0-9_.!~*'()%+-]{1,10}", option::of("[A-Za-z0-9]{1,6}"))),
+            host(),
+            option::of(any::<u16>()),
+            params(),
+            prop::collection::vec(("[A-Za-z][A-Za-z-]{0,7}", "[A-Za-z0-9%@.]{0,8}"), 0..3),
+        )
+            .prop_map(|(scheme, userinfo, host, port, params, headers)| {
+                let (user, password) = userinfo.map_or((None, None), |(user, password)| (Some(user), password));
+                SipUri { scheme, user, password, host, port, params, headers }
+            })
+    }
+
+    fn tel_uri() -> impl Strategy<Value = SipUri> {
+        ("\\+?[0-9][0-9-]{0,14}", params()).prop_map(|(number, params)| SipUri { params, ..SipUri::tel(number) })
+    }
+
+    fn name_addr() -> impl Strategy<Value = NameAddr> {
+        (
+            option::of(prop_oneof!["[A-Za-z0-9._~+-]{1,10}", "\\PC{1,16}"]),
+            prop_oneof![sip_uri(), tel_uri()],
+            params(),
+        )
+            .prop_map(|(display_name, uri, params)| NameAddr { display_name, uri, params })
+    }
+
+    proptest! {
+        #[test]
+        fn uri_round_trips(uri in prop_oneof![sip_uri(), tel_uri()]) {
+            prop_assert_eq!(SipUri::parse(&uri.to_string()), Ok(uri));
+        }
+
+        #[test]
+        fn name_addr_round_trips(addr in name_addr()) {
+            prop_assert_eq!(NameAddr::parse(&addr.to_string()), Ok(addr));
+        }
+
+        #[test]
+        fn reparsing_printed_input_is_stable(input in "\\PC{0,40}") {
+            if let Ok(addr) = NameAddr::parse(&input) {
+                prop_assert_eq!(NameAddr::parse(&addr.to_string()), Ok(addr));
+            }
+        }
+    }
+
+    #[test]
+    fn name_addr_example_round_trips() {
+        let addr = NameAddr::parse(r#""Bob" <sip:200@pbx:5080;transport=tcp>"#).unwrap();
+        assert_eq!(addr.display_name.as_deref(), Some("Bob"));
+        assert_eq!(addr.uri.user.as_deref(), Some("200"));
+        assert_eq!(addr.uri.host, "pbx");
+        assert_eq!(addr.uri.port, Some(5080));
+        assert_eq!(addr.uri.param("transport"), Some(Some("tcp")));
+        assert_eq!(addr.to_string(), "Bob <sip:200@pbx:5080;transport=tcp>");
+        assert_eq!(NameAddr::parse(&addr.to_string()), Ok(addr));
+    }
+
+    #[test]
+    fn display_names_that_are_not_one_token_stay_quoted() {
+        for name in ["Bob ", " Bob", "Bob Smith", "O\"Neil", "Zoë"] {
+            let addr = NameAddr {
+                display_name: Some(name.to_string()),
+                ..NameAddr::new(SipUri::parse("sip:a@b").unwrap())
+            };
+            assert!(addr.to_string().starts_with('"'), "{}", addr);
+            assert_eq!(NameAddr::parse(&addr.to_string()), Ok(addr));
+        }
+    }
+
+    #[test]
+    fn ipv6_hosts_must_be_bracketed() {
+        let uri = SipUri::parse("sip:[2001:db8::1]:5060").unwrap();
+        assert_eq!(uri.host, "2001:db8::1");
+        assert_eq!(uri.port, Some(5060));
+        assert_eq!(uri.to_string(), "sip:[2001:db8::1]:5060");
+
+        assert!(matches!(SipUri::parse("sip:2001:db8::1"), Err(UriError::InvalidHost(_))));
+        assert!(matches!(SipUri::parse("sip:alice@2001:db8::1:5060"), Err(UriError::InvalidHost(_))));
+        assert_eq!(SipUri::parse("sip:pbx:5080").unwrap().port, Some(5080));
+    }
+}
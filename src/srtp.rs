@@ -0,0 +1,83 @@
+//! SRTP media encryption policy with SDES key exchange (RFC 4568).
+//!
+//! Keys travel in SDP `a=crypto` lines, so SDES only protects media when the
+//! signalling is itself protected (TLS); the policy is about the media leg.
+
+/// Whether calls encrypt their media.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SrtpPolicy {
+    /// Plain RTP (`RTP/AVP`), crypto offers from the remote are ignored.
+    #[default]
+    Off,
+    /// Offer SRTP but fall back to RTP when the remote does not support it.
+    Optional,
+    /// SRTP only: unencrypted offers are rejected with 488 and calls whose
+    /// answer lacks keys are hung up.
+    Mandatory,
+}
+
+impl SrtpPolicy {
+    pub fn all() -> [Self; 3] {
+        [Self::Off, Self::Optional, Self::Mandatory]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off (RTP)",
+            Self::Optional => "Optional (SRTP when supported)",
+            Self::Mandatory => "Mandatory (SRTP only)",
+        }
+    }
+
+    /// Stable identifier used as the `<select>` value.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Optional => "optional",
+            Self::Mandatory => "mandatory",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Self {
+        Self::all()
+            .into_iter()
+            .find(|p| p.id() == id)
+            .unwrap_or_default()
+    }
+
+    /// Crypto suites offered in `a=crypto` lines, strongest first.
+    pub fn suites(self) -> &'static [&'static str] {
+        match self {
+            Self::Off => &[],
+            Self::Optional | Self::Mandatory => SUITES,
+        }
+    }
+
+    /// Why a call with (`encrypted`) or without encrypted media breaks this
+    /// policy, if it does.
+    pub fn violation(self, encrypted: bool) -> Option<&'static str> {
+        (self == Self::Mandatory && !encrypted)
+            .then_some("Encrypted media (SRTP) is required, but the other party does not support it")
+    }
+}
+
+/// SDES suites from RFC 4568 §6.2, in preference order.
+const SUITES: &[&str] = &["AES_CM_128_HMAC_SHA1_80", "AES_CM_128_HMAC_SHA1_32"];
+
+/// Whether an SDP offer proposes SRTP for audio: a secure `m=audio` profile
+/// (`RTP/SAVP`, `RTP/SAVPF`) or an `a=crypto` attribute in the audio section.
+pub fn sdp_offers_srtp(sdp: &str) -> bool {
+    let mut in_audio = false;
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            let mut fields = media.split_whitespace();
+            in_audio = fields.next() == Some("audio");
+            if in_audio && fields.nth(1).is_some_and(|proto| proto.contains("SAVP")) {
+                return true;
+            }
+        } else if in_audio && line.starts_with("a=crypto:") {
+            return true;
+        }
+    }
+    false
+}
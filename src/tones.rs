@@ -0,0 +1,257 @@
+//! Locally generated call-progress sounds: the ringtone for incoming calls
+//! and the ringback tone heard while an outgoing call rings without early
+//! media.
+//!
+//! Tones are sums of sine waves switched on and off by a cadence; a
+//! ringtone may instead be an audio file, looped.
+
+use std::f64::consts::TAU;
+use std::path::PathBuf;
+
+use log::warn;
+
+use crate::playback::{AudioClip, AudioInjector};
+
+/// A tone: frequencies played together, switched by a repeating cadence of
+/// (on, duration in ms) steps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    pub frequencies: &'static [f64],
+    pub cadence: &'static [(bool, u32)],
+    /// Peak amplitude, 0.0..=1.0
+    pub level: f64,
+}
+
+impl Tone {
+    fn period_ms(&self) -> u32 {
+        self.cadence.iter().map(|(_, ms)| ms).sum::<u32>().max(1)
+    }
+
+    fn is_on(&self, at_ms: u32) -> bool {
+        let mut at = at_ms % self.period_ms();
+        for &(on, ms) in self.cadence {
+            if at < ms {
+                return on;
+            }
+            at -= ms;
+        }
+        false
+    }
+}
+
+/// Built-in ringtone when no file is configured: a 1 s warble every 3 s.
+pub const RINGTONE: Tone = Tone {
+    frequencies: &[1000.0, 1250.0],
+    cadence: &[(true, 1000), (false, 2000)],
+    level: 0.5,
+};
+
+/// Country conventions for the ringback tone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RingbackLocale {
+    /// 440 + 480 Hz, 2 s on, 4 s off
+    #[default]
+    Us,
+    /// 400 + 450 Hz, 0.4 s on, 0.2 s off, 0.4 s on, 2 s off
+    Uk,
+    /// 425 Hz, 1 s on, 4 s off (CEPT)
+    Eu,
+}
+
+impl RingbackLocale {
+    pub fn all() -> [Self; 3] {
+        [Self::Us, Self::Uk, Self::Eu]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Us => "North America",
+            Self::Uk => "United Kingdom",
+            Self::Eu => "Europe (CEPT)",
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Us => "us",
+            Self::Uk => "uk",
+            Self::Eu => "eu",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "uk" => Self::Uk,
+            "eu" => Self::Eu,
+            _ => Self::Us,
+        }
+    }
+
+    pub fn tone(self) -> Tone {
+        match self {
+            Self::Us => Tone {
+                frequencies: &[440.0, 480.0],
+                cadence: &[(true, 2000), (false, 4000)],
+                level: 0.25,
+            },
+            Self::Uk => Tone {
+                frequencies: &[400.0, 450.0],
+                cadence: &[(true, 400), (false, 200), (true, 400), (false, 2000)],
+                level: 0.25,
+            },
+            Self::Eu => Tone {
+                frequencies: &[425.0],
+                cadence: &[(true, 1000), (false, 4000)],
+                level: 0.25,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RingConfig {
+    /// Ringtone file (WAV or Ogg Opus); the built-in tone when unset.
+    pub ringtone: Option<PathBuf>,
+    /// Output device to ring on; the system default when unset.
+    pub device: Option<String>,
+    pub ringback: RingbackLocale,
+}
+
+/// Generates a [`Tone`] at whatever rate the output asks for.
+#[derive(Debug, Clone)]
+pub struct ToneGenerator {
+    tone: Tone,
+    /// Seconds since the tone started
+    elapsed: f64,
+}
+
+impl ToneGenerator {
+    pub fn new(tone: Tone) -> Self {
+        Self { tone, elapsed: 0.0 }
+    }
+
+    pub fn fill(&mut self, frame: &mut [i16], sample_rate: u32) {
+        let step = 1.0 / f64::from(sample_rate.max(1));
+        let scale = self.tone.level * 32767.0 / self.tone.frequencies.len().max(1) as f64;
+        for out in frame.iter_mut() {
+            *out = if self.tone.is_on((self.elapsed * 1000.0) as u32) {
+                let sum: f64 = self
+                    .tone
+                    .frequencies
+                    .iter()
+                    .map(|f| (TAU * f * self.elapsed).sin())
+                    .sum();
+                (sum * scale) as i16
+            } else {
+                0
+            };
+            self.elapsed += step;
+        }
+    }
+}
+
+/// What a local tone output plays.
+#[derive(Debug)]
+pub enum ToneSource {
+    Generated(ToneGenerator),
+    /// A file, played through an injector's local path
+    File(AudioInjector),
+}
+
+impl ToneSource {
+    /// The configured ringtone file, loaded; `None` when it is unset or
+    /// cannot be played, and the built-in [`ringtone`](Self::ringtone) is
+    /// used instead.
+    pub fn ringtone_clip(config: &RingConfig) -> Option<AudioClip> {
+        let path = config.ringtone.as_ref()?;
+        AudioClip::load(path)
+            .inspect_err(|e| warn!("Ringtone {} cannot be played, using the built-in one: {}", path.display(), e))
+            .ok()
+    }
+
+    pub fn ringtone() -> Self {
+        Self::Generated(ToneGenerator::new(RINGTONE))
+    }
+
+    pub fn ringback(locale: RingbackLocale) -> Self {
+        Self::Generated(ToneGenerator::new(locale.tone()))
+    }
+
+    pub fn fill(&mut self, frame: &mut [i16], sample_rate: u32) {
+        match self {
+            Self::Generated(generator) => generator.fill(frame, sample_rate),
+            Self::File(injector) => {
+                frame.fill(0);
+                injector.process_incoming(frame, sample_rate);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::PlaybackOptions;
+
+    /// Peak level of `ms` of the generator's output at 8 kHz.
+    fn peak(generator: &mut ToneGenerator, ms: usize) -> i16 {
+        let mut frame = vec![0; 8 * ms];
+        generator.fill(&mut frame, 8000);
+        frame.iter().map(|s| s.saturating_abs()).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn cadences_repeat() {
+        let uk = RingbackLocale::Uk.tone();
+        for (at, on) in [(0, true), (399, true), (400, false), (600, true), (1000, false), (2999, false), (3000, true)] {
+            assert_eq!(uk.is_on(at), on, "{} ms", at);
+        }
+        assert!(RingbackLocale::Us.tone().is_on(6000));
+        assert!(!RingbackLocale::Eu.tone().is_on(1000));
+    }
+
+    #[test]
+    fn generated_tones_follow_the_cadence_at_their_level() {
+        let mut generator = ToneGenerator::new(RingbackLocale::Uk.tone());
+        let on = peak(&mut generator, 400);
+        assert!(on > 0 && f64::from(on) <= 0.25 * 32767.0, "{}", on);
+        assert_eq!(peak(&mut generator, 200), 0);
+        assert!(peak(&mut generator, 400) > 0);
+        assert_eq!(peak(&mut generator, 2000), 0);
+    }
+
+    #[test]
+    fn file_ringtones_loop_at_the_output_rate() {
+        let injector = AudioInjector::default();
+        let mut source = ToneSource::File(injector.clone());
+        let mut frame = [1; 160];
+        // Nothing plays until the clip is started
+        source.fill(&mut frame, 8000);
+        assert_eq!(frame, [0; 160]);
+
+        let clip = AudioClip {
+            sample_rate: 8000,
+            samples: (1..=100).collect(),
+        };
+        let options = PlaybackOptions {
+            local: true,
+            looped: true,
+            ..Default::default()
+        };
+        injector.play(clip, options, 8000);
+        source.fill(&mut frame, 8000);
+        assert_eq!(&frame[..3], [1, 2, 3]);
+        assert_eq!(&frame[99..102], [100, 1, 2]);
+    }
+
+    #[test]
+    fn unplayable_ringtones_fall_back_to_the_built_in_one() {
+        assert!(ToneSource::ringtone_clip(&RingConfig::default()).is_none());
+        let config = RingConfig {
+            ringtone: Some(PathBuf::from("/nonexistent/ringtone.wav")),
+            ..Default::default()
+        };
+        assert!(ToneSource::ringtone_clip(&config).is_none());
+        assert!(matches!(ToneSource::ringtone(), ToneSource::Generated(_)));
+    }
+}